@@ -0,0 +1,79 @@
+use nalgebra::{DefaultAllocator, DimName, MatrixMN, U1, U2, VectorN};
+use nalgebra::allocator::Allocator;
+
+use {Bra, Ket, Outer, Complex, TOLERANCE};
+
+/// Orthonormal basis of kets, e.g. to measure the state in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Basis<D: DimName>(Vec<Ket<D>>)
+    where DefaultAllocator: Allocator<Complex, D>;
+
+impl<D: DimName> Basis<D>
+    where DefaultAllocator: Allocator<Complex, D>
+{
+    /// Basis from the set of kets, which should be orthonormal and span the whole space.
+    pub fn new(kets: Vec<Ket<D>>) -> Result<Basis<D>, &'static str> {
+        if kets.len() != D::name().value() {
+            return Err("Number of basis kets should be equal to the dimension");
+        }
+
+        for (i, a) in kets.iter().enumerate() {
+            for (j, b) in kets.iter().enumerate().skip(i) {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                if (a.0.dotc(&b.0) - Complex::from(expected)).norm() > TOLERANCE {
+                    return Err(if i == j { "Basis kets should be normalized" } else { "Basis kets should be orthogonal" });
+                }
+            }
+        }
+
+        Ok(Basis(kets))
+    }
+
+    /// Computational basis, |0>, |1>, ...
+    pub fn computational() -> Basis<D> {
+        let dim = D::name().value();
+        Basis(
+            (0..dim).map(|i| Ket(VectorN::from_fn_generic(D::name(), U1, |j, _| if i == j { 1.0.into() } else { 0.0.into() })))
+                .collect()
+        )
+    }
+
+    /// Kets of the basis
+    pub fn kets(&self) -> &[Ket<D>] {
+        &self.0
+    }
+
+    /// Coordinates of the ket in this basis, i.e. `<b_i|ψ>`
+    pub fn coordinates(&self, ket: &Ket<D>) -> Ket<D> {
+        Ket(VectorN::from_iterator_generic(D::name(), U1, self.0.iter().map(|b| b.0.dotc(&ket.0))))
+    }
+
+    /// Change-of-basis operator `Σ |b_i><a_i|`, taking every ket of this basis to the
+    /// corresponding ket of the `other` one.
+    pub fn change_to(&self, other: &Basis<D>) -> Outer<D>
+        where DefaultAllocator: Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+    {
+        self.0.iter().zip(other.0.iter())
+            .fold(
+                Outer(MatrixMN::zeros_generic(D::name(), D::name())),
+                |acc, (a, b)| acc + b * Bra::from(a.clone()),
+            )
+    }
+}
+
+impl Basis<U2> {
+    /// Z basis (up, down)
+    pub fn z() -> Basis<U2> {
+        Basis(vec![Ket::<U2>::up(), Ket::<U2>::down()])
+    }
+
+    /// X basis (right, left)
+    pub fn x() -> Basis<U2> {
+        Basis(vec![Ket::<U2>::right(), Ket::<U2>::left()])
+    }
+
+    /// Y basis (inward, outward)
+    pub fn y() -> Basis<U2> {
+        Basis(vec![Ket::<U2>::inw(), Ket::<U2>::out()])
+    }
+}
@@ -0,0 +1,234 @@
+//! Interactive shell for Dirac expressions.
+//!
+//! `braket` starts the REPL reading from the standard input, `braket script.bk` runs the
+//! script file line by line and prints the results, stopping at the first error.
+
+extern crate brakets;
+extern crate rand;
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+
+use brakets::parse::{self, Context, ParseError, Value};
+use brakets::{DKet, Ket};
+use rand::rngs::StdRng;
+use rand::{FromEntropy, SeedableRng};
+
+const HELP: &str = "\
+Expressions are evaluated and printed, e.g. (|0> + |1>)/sqrt(2) or <+|H|0>
+
+  let NAME = EXPR    bind the value of the expression to the name
+  :probs EXPR        probabilities of the measurement in the computational basis
+  :bloch EXPR        Bloch vector of the qubit state
+  :measure EXPR N    measure N copies of the state
+  :seed N            seed the random generator of the measurements
+  :history           list previous inputs
+  :help              show this help
+  :quit              exit";
+
+/// Failure of a single input line, either with a position in the line or without one.
+enum Error {
+    Parse(ParseError, usize),
+    Message(String),
+}
+
+impl From<&'static str> for Error {
+    fn from(message: &'static str) -> Self {
+        Error::Message(message.to_owned())
+    }
+}
+
+struct Session {
+    context: Context,
+    history: Vec<String>,
+    rng: StdRng,
+}
+
+/// Check that the name bound with `let` can be read back: alphabetic start, then alphanumeric
+/// or `_`, and neither a keyword, a label of basis symbols nor a built-in operator.
+fn check_name(name: &str) -> Result<(), Error> {
+    let syntax = name.chars().next().is_some_and(char::is_alphabetic)
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let message = if !syntax {
+        format!("Invalid name `{}`", name)
+    } else if parse::KEYWORDS.contains(&name) {
+        format!("Name `{}` is reserved", name)
+    } else if parse::is_label(name) {
+        format!("Name `{}` is made of basis symbols, |{}> would be a basis state", name, name)
+    } else if Context::new().get(name).is_some() {
+        format!("Name `{}` is a built-in operator", name)
+    } else {
+        return Ok(());
+    };
+    Err(Error::Message(message))
+}
+
+impl Session {
+    fn new() -> Session {
+        Session { context: Context::new(), history: Vec::new(), rng: StdRng::from_entropy() }
+    }
+
+    /// Evaluate the expression located at `offset` bytes into the input line.
+    fn evaluate(&self, line: &str, offset: usize) -> Result<Value, Error> {
+        self.context.evaluate_str(&line[offset..]).map_err(|error| Error::Parse(error, offset))
+    }
+
+    fn ket(&self, line: &str, offset: usize) -> Result<DKet, Error> {
+        match self.evaluate(line, offset)? {
+            Value::Ket(ket) => {
+                if ket.clone().into_vector().norm() == 0.0 {
+                    return Err("State should be non-zero".into());
+                }
+                Ok(ket)
+            },
+            _ => Err("Expression should evaluate to a ket".into()),
+        }
+    }
+
+    /// Execute the input line, returning the text to print (if any).
+    fn execute(&mut self, line: &str) -> Result<Option<String>, Error> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return Ok(None);
+        }
+        self.history.push(trimmed.to_owned());
+
+        // offset of the command argument in the line
+        let start = line.len() - line.trim_start().len();
+        let argument = |command: &str| {
+            let rest = &line[start + command.len()..];
+            start + command.len() + (rest.len() - rest.trim_start().len())
+        };
+
+        let command = trimmed.split_whitespace().next().unwrap_or("");
+        match command {
+            "let" => {
+                let offset = argument("let");
+                let equals = line[offset..].find('=').ok_or("Expected `let NAME = EXPR`")?;
+                let name = line[offset..offset + equals].trim();
+                check_name(name)?;
+                let value = self.evaluate(line, offset + equals + 1)?;
+                let text = format!("{} = {}", name, value);
+                self.context.define(name, value);
+                Ok(Some(text))
+            },
+            ":probs" => {
+                let ket = self.ket(line, argument(command))?;
+                let dim = ket.dim();
+                let lines: Vec<String> = ket.probabilities().iter().enumerate()
+                    .map(|(i, p)| format!("{}: {:.4}", label(i, dim), p))
+                    .collect();
+                Ok(Some(lines.join("\n")))
+            },
+            ":bloch" => {
+                let ket = Ket::try_from(self.ket(line, argument(command))?)
+                    .map_err(|_| "Bloch vector is defined only for a single qubit")?;
+                let r = ket.bloch_vector();
+                Ok(Some(format!("({:.4}, {:.4}, {:.4})", r.x, r.y, r.z)))
+            },
+            ":measure" => {
+                let offset = argument(command);
+                let end = line.trim_end().len();
+                let split = line[..end].rfind(char::is_whitespace).filter(|&i| i >= offset)
+                    .ok_or("Expected `:measure EXPR N`")?;
+                let shots = line[split..end].trim().parse().ok().filter(|&shots| shots > 0)
+                    .ok_or("Number of shots should be a positive integer")?;
+                let ket = self.ket(&line[..split], offset)?;
+                let histogram = ket.sample(shots, &mut self.rng);
+                let lines: Vec<String> = histogram.iter().map(|(label, count)| format!("|{}⟩: {}", label, count)).collect();
+                Ok(Some(lines.join("\n")))
+            },
+            ":seed" => {
+                let seed = trimmed[command.len()..].trim().parse().map_err(|_| "Seed should be a non-negative integer")?;
+                self.rng = StdRng::seed_from_u64(seed);
+                Ok(None)
+            },
+            ":history" => {
+                let lines: Vec<String> = self.history.iter().enumerate()
+                    .map(|(i, input)| format!("{:4}  {}", i + 1, input))
+                    .collect();
+                Ok(Some(lines.join("\n")))
+            },
+            ":help" => Ok(Some(HELP.to_owned())),
+            _ if command.starts_with(':') => Err(Error::Message(format!("Unknown command `{}`, see :help", command))),
+            _ => self.evaluate(line, 0).map(|value| Some(value.to_string())),
+        }
+    }
+}
+
+// Basis state label of the same form as in the Dirac output
+fn label(index: usize, dim: usize) -> String {
+    DKet::basis(dim, index).map(|ket| ket.to_string()).unwrap_or_else(|_| index.to_string())
+}
+
+/// Error message pointing at the span of the parse error in the line.
+fn report(line: &str, error: &Error) -> String {
+    match *error {
+        Error::Message(ref message) => format!("ERROR: {}", message),
+        Error::Parse(ref error, offset) => {
+            // count characters rather than bytes
+            let (start, end) = (offset + error.span.start, offset + error.span.end);
+            let indent = line[..start].chars().count();
+            let width = line[start..end].chars().count().max(1);
+            format!("ERROR: {} at {}..{}\n{}\n{}{}", error.kind, start, end, line, " ".repeat(indent), "^".repeat(width))
+        },
+    }
+}
+
+fn run_script(path: &str) {
+    let script = fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("Cannot read {}: {}", path, error);
+        process::exit(2);
+    });
+
+    let mut session = Session::new();
+    for (number, line) in script.lines().enumerate() {
+        match session.execute(line) {
+            Ok(Some(text)) => println!("{}", text),
+            Ok(None) => {},
+            Err(error) => {
+                eprintln!("{}:{}: {}", path, number + 1, report(line, &error));
+                process::exit(1);
+            },
+        }
+    }
+}
+
+fn run_interactive() {
+    let stdin = io::stdin();
+    let mut session = Session::new();
+    let mut line = String::new();
+
+    loop {
+        print!("braket> ");
+        io::stdout().flush().expect("stdout is writable");
+
+        line.clear();
+        if stdin.lock().read_line(&mut line).expect("stdin is readable") == 0 {
+            println!();
+            break;
+        }
+        let input = line.trim_end_matches(['\n', '\r']);
+        if input.trim() == ":quit" || input.trim() == ":q" {
+            break;
+        }
+
+        // a panic on one input should not end the session
+        match panic::catch_unwind(AssertUnwindSafe(|| session.execute(input))) {
+            Ok(Ok(Some(text))) => println!("{}", text),
+            Ok(Ok(None)) => {},
+            Ok(Err(error)) => println!("{}", report(input, &error)),
+            Err(_) => println!("ERROR: evaluation failed, the input is skipped"),
+        }
+    }
+}
+
+fn main() {
+    match ::std::env::args().nth(1) {
+        Some(path) => run_script(&path),
+        None => run_interactive(),
+    }
+}
@@ -0,0 +1,74 @@
+use nalgebra::{Matrix2, U2, Vector2, Vector3};
+
+use {DensityMatrix, Ket, Outer, Outer2, Complex, TOLERANCE};
+
+// Pauli X, Y, Z operators, in the order of Bloch vector components
+fn paulis() -> [Outer<U2>; 3] {
+    [Outer2::n2(), Outer2::y2(), Outer2::z2()]
+}
+
+impl Ket<U2> {
+    /// Unit Bloch vector of the (normalized) qubit state: up/down are ±z, right/left are ±x,
+    /// inw/out are ±y
+    pub fn bloch_vector(&self) -> Vector3<f64> {
+        DensityMatrix::pure(self).bloch_vector()
+    }
+
+    /// Qubit state with the polar angle `theta` and azimuthal angle `phi` on the Bloch sphere,
+    /// `cos(θ/2)|0> + e^{iφ} sin(θ/2)|1>`
+    pub fn from_bloch(theta: f64, phi: f64) -> Ket<U2> {
+        Ket(Vector2::new(
+            Complex::from((theta / 2.0).cos()),
+            Complex::from_polar(&(theta / 2.0).sin(), &phi),
+        ))
+    }
+}
+
+impl DensityMatrix<U2> {
+    /// Bloch vector `(Tr ρX, Tr ρY, Tr ρZ)`, of unit length only for pure states
+    pub fn bloch_vector(&self) -> Vector3<f64> {
+        let [x, y, z] = paulis();
+        Vector3::new(self.expectation(&x).re, self.expectation(&y).re, self.expectation(&z).re)
+    }
+
+    /// Qubit state with the Bloch vector `r` (within the unit ball), `(I + r·σ)/2`
+    pub fn from_bloch_vector(r: &Vector3<f64>) -> Result<DensityMatrix<U2>, &'static str> {
+        if r.norm() > 1.0 + TOLERANCE {
+            return Err("Bloch vector should be within the unit ball");
+        }
+        let [x, y, z] = paulis();
+        Ok(DensityMatrix((Outer2::identity() + x * r.x + y * r.y + z * r.z).0 / Complex::from(2.0)))
+    }
+}
+
+impl Outer<U2> {
+    /// Rotation of the Bloch sphere performed by the unitary operator: unit axis and angle
+    /// in `[0, π]`, so that `U = e^{iα} (cos(θ/2) I - i sin(θ/2) n·σ)`.
+    ///
+    /// Axis of the identity rotation is chosen to be z.
+    pub fn rotation(&self) -> Result<(Vector3<f64>, f64), &'static str> {
+        if (self.0.adjoint() * self.0 - Matrix2::identity()).iter().any(|v| v.norm() > TOLERANCE) {
+            return Err("Operator should be unitary");
+        }
+
+        // special unitary V = U / √det U, sign chosen to make Tr(V)/2 = cos(θ/2) non-negative
+        let mut special = self.0 / self.determinant().sqrt();
+        if special.trace().re < 0.0 {
+            special = -special;
+        }
+
+        let [x, y, z] = paulis();
+        let half_cos = (special.trace().re / 2.0).min(1.0);
+        let axis = Vector3::new(
+            (Complex::i() * (special * x.0).trace()).re / 2.0,
+            (Complex::i() * (special * y.0).trace()).re / 2.0,
+            (Complex::i() * (special * z.0).trace()).re / 2.0,
+        );
+
+        let angle = 2.0 * half_cos.acos();
+        if axis.norm() < TOLERANCE {
+            return Ok((Vector3::z(), 0.0));
+        }
+        Ok((axis.normalize(), angle))
+    }
+}
@@ -1,8 +1,7 @@
-use std::ops::{Mul, Add};
-use nalgebra::{DefaultAllocator, U1, RowVector2, RowVectorN, DimName, U2};
+use nalgebra::{DefaultAllocator, U1, RowVector2, RowVectorN, DimName, U2, DimNameProd, DimNameMul, Matrix};
 use nalgebra::allocator::Allocator;
 
-use {SQRT_2_INVERSE, Ket, Outer, Complex};
+use {dirac, SQRT_2_INVERSE, Ket, Outer, Complex};
 
 /// Generic Bra. You can multiply it by ket to get an inner product (scalar).
 #[derive(Clone, Debug, PartialEq)]
@@ -71,50 +70,74 @@ impl<D: DimName> Bra<D>
             )
         )
     }
-}
-
-impl<D: DimName> Mul<Ket<D>> for Bra<D>
-    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, U1, D>
-{
-    type Output = Complex;
 
-    fn mul(self, other: Ket<D>) -> Self::Output {
-        let mut m = self.0;
-        for f in m.iter_mut() { *f = f.conj(); }
-        (m * other.0)[0]
+    /// Kronecker (tensor) product of bras of arbitrary dimensions
+    ///
+    /// `<a| ⊗ <b|`, where `self` occupies the most significant position.
+    pub fn tensor<B: DimName>(self, other: Bra<B>) -> Bra<DimNameProd<D, B>>
+        where
+            D: DimNameMul<B>,
+            DefaultAllocator: Allocator<Complex, U1, B> + Allocator<Complex, U1, DimNameProd<D, B>>
+    {
+        let mut result = Matrix::zeros_generic(U1, DimNameProd::<D, B>::name());
+        let dim_a = D::name().value();
+        let dim_b = B::name().value();
+        for i in 0..dim_a {
+            for j in 0..dim_b {
+                *result.get_mut(
+                    i*dim_b + j
+                ).expect("i, j in (dim_a, dim_b) range") = self.0[i] * other.0[j];
+            }
+        }
+        Bra(result)
     }
 }
 
-// Only bra can be multiplied my square matrix
-impl<D: DimName> Mul<Outer<D>> for Bra<D>
-    where DefaultAllocator: Allocator<Complex, D, D> + Allocator<Complex, U1, D>
-{
-    type Output = Self;
-
-    fn mul(self, other: Outer<D>) -> Self::Output {
-        Bra(self.0 * other.0)
-    }
-}
-
-impl<D: DimName> Add for Bra<D>
-    where DefaultAllocator: Allocator<Complex, U1, D>
-{
-    type Output = Self;
+impl_binop!(
+    [D: DimName] Mul, mul for Bra<D>, Ket<D> => Complex
+    where [DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, U1, D>]
+    |bra, ket| (bra.0.conjugate() * &ket.0)[0]
+);
 
-    fn add(self, other: Bra<D>) -> Self::Output {
-        Bra(self.0 + other.0)
-    }
-}
-
-impl<D: DimName> Mul<Complex> for Bra<D>
-    where DefaultAllocator: Allocator<Complex, U1, D>
-{
-    type Output = Self;
-
-    fn mul(self, other: Complex) -> Self::Output {
-        Bra(self.0 * other)
-    }
-}
+// Only bra can be multiplied my square matrix
+//
+// Bra keeps the components of its dual ket, so `<a|A` is stored as the dual of `A†|a>`.
+impl_binop!(
+    [D: DimName] Mul, mul for Bra<D>, Outer<D> => Bra<D>
+    where [DefaultAllocator: Allocator<Complex, D, D> + Allocator<Complex, U1, D>]
+    |bra, outer| Bra(&bra.0 * outer.0.conjugate())
+);
+
+impl_binop!(
+    [D: DimName] Add, add for Bra<D>, Bra<D> => Bra<D>
+    where [DefaultAllocator: Allocator<Complex, U1, D>]
+    |a, b| Bra(&a.0 + &b.0)
+);
+
+impl_binop!(
+    [D: DimName] Sub, sub for Bra<D>, Bra<D> => Bra<D>
+    where [DefaultAllocator: Allocator<Complex, U1, D>]
+    |a, b| Bra(&a.0 - &b.0)
+);
+
+impl_neg!(
+    [D: DimName] for Bra<D>
+    where [DefaultAllocator: Allocator<Complex, U1, D>]
+    |a| Bra(-&a.0)
+);
+
+// Scaling is applied to the bra itself, i.e. to the conjugate of the stored components.
+impl_scalar_op!(
+    [D: DimName] Mul, mul for Bra<D>, Complex => Bra<D>
+    where [DefaultAllocator: Allocator<Complex, U1, D>]
+    |bra, scalar| Bra(&bra.0 * scalar.conj())
+);
+
+impl_scalar_op!(
+    [D: DimName] Div, div for Bra<D>, Complex => Bra<D>
+    where [DefaultAllocator: Allocator<Complex, U1, D>]
+    |bra, scalar| Bra(&bra.0 / scalar.conj())
+);
 
 impl<D: DimName> From<Ket<D>> for Bra<D>
     where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
@@ -133,11 +156,10 @@ impl<D: DimName> From<RowVectorN<Complex, D>> for Bra<D>
 }
 
 impl<D: DimName> ::std::fmt::Display for Bra<D>
-    where DefaultAllocator: Allocator<Complex, U1, D>,
-    DefaultAllocator: Allocator<usize, U1, D>
+    where DefaultAllocator: Allocator<Complex, U1, D>
 {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        write!(f, "{}", self.0)
+        f.write_str(&self.to_dirac(&dirac::formatter_options(f)))
     }
 }
 
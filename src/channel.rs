@@ -0,0 +1,232 @@
+use nalgebra::{DefaultAllocator, DMatrix, DimName, DimNameMul, DimNameProd, Matrix2, MatrixMN, U2};
+use nalgebra::allocator::Allocator;
+
+use {linalg, DensityMatrix, Outer, Outer2, Complex, TOLERANCE};
+
+/// Quantum channel (completely positive trace preserving map) given by Kraus operators,
+/// `ρ → Σ K_i ρ K_i†` with `Σ K_i† K_i = I`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel<D: DimName>(Vec<Outer<D>>)
+    where DefaultAllocator: Allocator<Complex, D, D>;
+
+// Pauli string `P_index` over `n` qubits, digits of the index in base 4 select I, X, Y, Z
+// (the most significant digit is qubit 0)
+fn pauli_string(index: usize, n: usize) -> DMatrix<Complex> {
+    let paulis = [Outer2::identity(), Outer2::n2(), Outer2::y2(), Outer2::z2()];
+    (0..n).fold(DMatrix::identity(1, 1), |acc, q| {
+        let digit = (index >> (2 * (n - 1 - q))) & 3;
+        acc.kronecker(&linalg::to_dynamic(&paulis[digit].0))
+    })
+}
+
+// Kraus operators of the channel with the Choi matrix `choi` of a `dim` dimensional system
+fn kraus_from_choi(choi: DMatrix<Complex>, dim: usize) -> Result<Vec<DMatrix<Complex>>, &'static str> {
+    if !linalg::is_hermitian(&choi) {
+        return Err("Choi matrix should be hermitian");
+    }
+    let (eigenvalues, eigenvectors) = linalg::hermitian_eigen(choi);
+    if eigenvalues.iter().any(|&v| v < -TOLERANCE) {
+        return Err("Choi matrix should be positive semi-definite");
+    }
+
+    // |K>> = Σ |i> ⊗ K|i>, so K_ji is the (i, j) component of the eigenvector
+    Ok(eigenvalues.iter().enumerate()
+        .filter(|&(_, &v)| v > TOLERANCE)
+        .map(|(k, &v)| DMatrix::from_fn(dim, dim, |j, i| eigenvectors[(i * dim + j, k)] * v.sqrt()))
+        .collect())
+}
+
+impl<D: DimName> Channel<D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    /// Channel from Kraus operators, which should satisfy `Σ K_i† K_i = I`
+    pub fn new(kraus: Vec<Outer<D>>) -> Result<Channel<D>, &'static str> {
+        let dim = D::name().value();
+        let sum = kraus.iter()
+            .fold(DMatrix::zeros(dim, dim), |acc, k| acc + linalg::to_dynamic(&(k.0.adjoint() * &k.0)));
+        if (sum - DMatrix::identity(dim, dim)).iter().any(|v| v.norm() > TOLERANCE) {
+            return Err("Kraus operators should be trace preserving");
+        }
+        Ok(Channel(kraus))
+    }
+
+    /// Identity channel, leaving the state intact
+    pub fn identity() -> Channel<D> {
+        Channel(vec![Outer::identity()])
+    }
+
+    /// Channel of the unitary evolution, `ρ → UρU†` (operator should be unitary)
+    pub fn unitary(unitary: Outer<D>) -> Result<Channel<D>, &'static str> {
+        Channel::new(vec![unitary])
+    }
+
+    /// Kraus operators of the channel
+    pub fn kraus(&self) -> &[Outer<D>] {
+        &self.0
+    }
+
+    /// State after the channel, `Σ K_i ρ K_i†`
+    pub fn apply(&self, state: &DensityMatrix<D>) -> DensityMatrix<D> {
+        DensityMatrix(self.apply_matrix(&state.0))
+    }
+
+    fn apply_matrix(&self, m: &MatrixMN<Complex, D, D>) -> MatrixMN<Complex, D, D> {
+        let mut result = m * Complex::from(0.0);
+        for k in &self.0 {
+            result += &k.0 * m * k.0.adjoint();
+        }
+        result
+    }
+
+    /// Channel applying `self` first and `next` after it
+    pub fn then(&self, next: &Channel<D>) -> Channel<D> {
+        Channel(next.0.iter()
+            .flat_map(|b| self.0.iter().map(move |a| b * a))
+            .collect())
+    }
+
+    /// Channel acting independently on two systems, `E ⊗ F`, where `self` acts on the most
+    /// significant subsystem
+    pub fn tensor<B: DimName>(&self, other: &Channel<B>) -> Channel<DimNameProd<D, B>>
+        where
+            D: DimNameMul<B>,
+            DefaultAllocator: Allocator<Complex, B, B> + Allocator<Complex, DimNameProd<D, B>, DimNameProd<D, B>>
+    {
+        Channel(self.0.iter()
+            .flat_map(|a| other.0.iter().map(move |b| a.clone().tensor(b.clone())))
+            .collect())
+    }
+
+    /// Choi matrix `Σ |i><j| ⊗ E(|i><j|)`
+    pub fn choi(&self) -> Outer<DimNameProd<D, D>>
+        where
+            D: DimNameMul<D>,
+            DefaultAllocator: Allocator<Complex, DimNameProd<D, D>, DimNameProd<D, D>>
+    {
+        let dim = D::name().value();
+        let choi = self.0.iter().fold(DMatrix::zeros(dim * dim, dim * dim), |acc, k| {
+            let vectorized = DMatrix::from_fn(dim * dim, 1, |r, _| k.0[(r % dim, r / dim)]);
+            acc + &vectorized * vectorized.adjoint()
+        });
+        Outer(linalg::from_dynamic(&choi))
+    }
+
+    /// Channel of the Choi matrix `Σ |i><j| ⊗ E(|i><j|)`
+    ///
+    /// Fails if the matrix does not define a completely positive trace preserving map.
+    pub fn from_choi(choi: &Outer<DimNameProd<D, D>>) -> Result<Channel<D>, &'static str>
+        where
+            D: DimNameMul<D>,
+            DefaultAllocator: Allocator<Complex, DimNameProd<D, D>, DimNameProd<D, D>>
+    {
+        let dim = D::name().value();
+        let kraus = kraus_from_choi(linalg::to_dynamic(&choi.0), dim)?;
+        Channel::new(kraus.iter().map(|k| Outer(linalg::from_dynamic(k))).collect())
+    }
+
+    /// Pauli transfer matrix `R_ij = Tr(P_i E(P_j)) / d` of the channel on a qubit register
+    ///
+    /// Pauli strings are ordered by base 4 digits I, X, Y, Z, qubit 0 being the most significant.
+    pub fn pauli_transfer_matrix(&self) -> Result<DMatrix<f64>, &'static str> {
+        let dim = D::name().value();
+        let n = linalg::qubit_count(dim)?;
+        let paulis: Vec<DMatrix<Complex>> = (0..dim * dim).map(|i| pauli_string(i, n)).collect();
+
+        Ok(DMatrix::from_fn(dim * dim, dim * dim, |i, j| {
+            let image = self.apply_matrix(&linalg::from_dynamic(&paulis[j]));
+            (&paulis[i] * linalg::to_dynamic(&image)).trace().re / dim as f64
+        }))
+    }
+
+    /// Channel of the Pauli transfer matrix on a qubit register, see `pauli_transfer_matrix`
+    ///
+    /// Fails if the matrix does not define a completely positive trace preserving map.
+    pub fn from_pauli_transfer_matrix(ptm: &DMatrix<f64>) -> Result<Channel<D>, &'static str> {
+        let dim = D::name().value();
+        let n = linalg::qubit_count(dim)?;
+        if ptm.nrows() != dim * dim || ptm.ncols() != dim * dim {
+            return Err("Pauli transfer matrix should be d² × d²");
+        }
+        let paulis: Vec<DMatrix<Complex>> = (0..dim * dim).map(|i| pauli_string(i, n)).collect();
+
+        // E(ρ) = Σ R_ij Tr(P_j ρ) P_i / d, applied to every |a><b| to assemble the Choi matrix
+        let mut choi = DMatrix::zeros(dim * dim, dim * dim);
+        for a in 0..dim {
+            for b in 0..dim {
+                let image = (0..dim * dim).fold(DMatrix::zeros(dim, dim), |acc, i| {
+                    let weight = (0..dim * dim)
+                        .fold(Complex::from(0.0), |w, j| w + paulis[j][(b, a)] * ptm[(i, j)]);
+                    acc + &paulis[i] * (weight / dim as f64)
+                });
+                for r in 0..dim {
+                    for c in 0..dim {
+                        choi[(a * dim + r, b * dim + c)] = image[(r, c)];
+                    }
+                }
+            }
+        }
+
+        let kraus = kraus_from_choi(choi, dim)?;
+        Channel::new(kraus.iter().map(|k| Outer(linalg::from_dynamic(k))).collect())
+    }
+}
+
+fn probability(p: f64) -> Result<f64, &'static str> {
+    if !(0.0..=1.0).contains(&p) {
+        return Err("Probability should be between 0 and 1");
+    }
+    Ok(p)
+}
+
+impl Channel<U2> {
+    /// Pauli channel, applying X, Y and Z with probabilities `px`, `py` and `pz`
+    pub fn pauli(px: f64, py: f64, pz: f64) -> Result<Channel<U2>, &'static str> {
+        let identity = 1.0 - probability(px)? - probability(py)? - probability(pz)?;
+        if identity < -TOLERANCE {
+            return Err("Pauli probabilities should not sum up over 1");
+        }
+        let terms = [
+            (identity.max(0.0), Outer2::identity()),
+            (px, Outer2::n2()),
+            (py, Outer2::y2()),
+            (pz, Outer2::z2()),
+        ];
+        Channel::new(terms.iter()
+            .filter(|&&(p, _)| p > 0.0)
+            .map(|&(p, ref pauli)| pauli * p.sqrt())
+            .collect())
+    }
+
+    /// Bit flip channel, applying X with probability `p`
+    pub fn bit_flip(p: f64) -> Result<Channel<U2>, &'static str> {
+        Channel::pauli(p, 0.0, 0.0)
+    }
+
+    /// Phase flip channel, applying Z with probability `p`
+    pub fn phase_flip(p: f64) -> Result<Channel<U2>, &'static str> {
+        Channel::pauli(0.0, 0.0, p)
+    }
+
+    /// Depolarizing channel, `ρ → (1 - p)ρ + p I/2`
+    pub fn depolarizing(p: f64) -> Result<Channel<U2>, &'static str> {
+        probability(p).and_then(|p| Channel::pauli(p / 4.0, p / 4.0, p / 4.0))
+    }
+
+    /// Amplitude damping channel, decaying |1> to |0> with probability `gamma`
+    pub fn amplitude_damping(gamma: f64) -> Result<Channel<U2>, &'static str> {
+        let gamma = probability(gamma)?;
+        Channel::new(vec![
+            Outer(Matrix2::new(1.0.into(), 0.0.into(), 0.0.into(), (1.0 - gamma).sqrt().into())),
+            Outer(Matrix2::new(0.0.into(), gamma.sqrt().into(), 0.0.into(), 0.0.into())),
+        ])
+    }
+
+    /// Phase damping channel, losing the phase of |1> with probability `lambda`
+    pub fn phase_damping(lambda: f64) -> Result<Channel<U2>, &'static str> {
+        let lambda = probability(lambda)?;
+        Channel::new(vec![
+            Outer(Matrix2::new(1.0.into(), 0.0.into(), 0.0.into(), (1.0 - lambda).sqrt().into())),
+            Outer(Matrix2::new(0.0.into(), 0.0.into(), 0.0.into(), lambda.sqrt().into())),
+        ])
+    }
+}
@@ -0,0 +1,303 @@
+use std::convert::TryFrom;
+
+use nalgebra::{DefaultAllocator, DimName};
+use nalgebra::allocator::Allocator;
+
+use {linalg, Complex, DKet, DOuter, Ket, Outer, Outer2, Outer4, U8};
+
+/// Single gate of the circuit, applied to the chosen qubits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instruction {
+    name: String,
+    gate: DOuter,
+    qubits: Vec<usize>,
+    // pushed by a named method rather than `Circuit::gate`
+    builtin: bool,
+}
+
+impl Instruction {
+    /// Gate name, e.g. "H" or "CNOT"
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gate operator
+    pub fn gate(&self) -> &DOuter {
+        &self.gate
+    }
+
+    /// Qubits the gate acts on, the first one is mapped to the most significant qubit of the gate
+    pub fn qubits(&self) -> &[usize] {
+        &self.qubits
+    }
+
+    // Whether the gate was pushed by a named method, so its name identifies the operator
+    pub(crate) fn is_builtin(&self) -> bool {
+        self.builtin
+    }
+}
+
+/// Quantum circuit over a register of qubits.
+///
+/// Qubit 0 is the most significant (leftmost) one in the register ket. Gates are pushed with
+/// chained calls, e.g. `circuit.h(0).cnot(0, 1).rz(1, theta)`, and applied in the pushed order.
+/// Named gate methods panic if qubits are out of the register range or repeated, use `gate`
+/// to get an error instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Circuit {
+    n_qubits: usize,
+    instructions: Vec<Instruction>,
+}
+
+impl Circuit {
+    /// Empty circuit over `n_qubits` qubits
+    pub fn new(n_qubits: usize) -> Circuit {
+        Circuit { n_qubits, instructions: Vec::new() }
+    }
+
+    /// Number of qubits in the register
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    /// Gates of the circuit in the order of application
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Push arbitrary gate acting on `qubits`
+    ///
+    /// Diagrams draw it as a box labeled with `name`, even if the name is one of the named gates.
+    pub fn gate<G: Into<DOuter>>(&mut self, name: &str, gate: G, qubits: &[usize]) -> Result<&mut Self, &'static str> {
+        self.insert(name, gate.into(), qubits, false)
+    }
+
+    fn insert(&mut self, name: &str, gate: DOuter, qubits: &[usize], builtin: bool) -> Result<&mut Self, &'static str> {
+        if qubits.is_empty() {
+            return Err("Gate should act on at least one qubit");
+        }
+        if qubits.len() > self.n_qubits {
+            return Err("Gate acts on more qubits than the register has");
+        }
+        if gate.dim() != 1 << qubits.len() {
+            return Err("Gate dimension does not match the number of qubits it is applied to");
+        }
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                return Err("Qubit index is out of the register range");
+            }
+            if qubits[..i].contains(&q) {
+                return Err("Gate cannot be applied to the same qubit twice");
+            }
+        }
+
+        self.instructions.push(Instruction { name: name.to_owned(), gate, qubits: qubits.to_vec(), builtin });
+        Ok(self)
+    }
+
+    fn push<G: Into<DOuter>>(&mut self, name: &str, gate: G, qubits: &[usize]) -> &mut Self {
+        if let Err(e) = self.insert(name, gate.into(), qubits, true) {
+            panic!("Invalid {} gate on qubits {:?}: {}", name, qubits, e);
+        }
+        self
+    }
+
+    /// Hadamard gate
+    pub fn h(&mut self, qubit: usize) -> &mut Self {
+        self.push("H", Outer2::h2(), &[qubit])
+    }
+
+    /// Pauli X (N) gate
+    pub fn x(&mut self, qubit: usize) -> &mut Self {
+        self.push("X", Outer2::n2(), &[qubit])
+    }
+
+    /// Pauli Y gate
+    pub fn y(&mut self, qubit: usize) -> &mut Self {
+        self.push("Y", Outer2::y2(), &[qubit])
+    }
+
+    /// Pauli Z gate
+    pub fn z(&mut self, qubit: usize) -> &mut Self {
+        self.push("Z", Outer2::z2(), &[qubit])
+    }
+
+    /// S (√Z) gate
+    pub fn s(&mut self, qubit: usize) -> &mut Self {
+        self.push("S", Outer2::s2(), &[qubit])
+    }
+
+    /// S† gate
+    pub fn sdg(&mut self, qubit: usize) -> &mut Self {
+        self.push("S†", Outer2::sdg2(), &[qubit])
+    }
+
+    /// T (π/8) gate
+    pub fn t(&mut self, qubit: usize) -> &mut Self {
+        self.push("T", Outer2::t2(), &[qubit])
+    }
+
+    /// T† gate
+    pub fn tdg(&mut self, qubit: usize) -> &mut Self {
+        self.push("T†", Outer2::tdg2(), &[qubit])
+    }
+
+    /// √X gate
+    pub fn sx(&mut self, qubit: usize) -> &mut Self {
+        self.push("√X", Outer2::sx2(), &[qubit])
+    }
+
+    /// Phase gate, diag(1, e^iφ)
+    pub fn p(&mut self, qubit: usize, phi: f64) -> &mut Self {
+        self.push("P", Outer2::p2(phi), &[qubit])
+    }
+
+    /// Rotation around X axis
+    pub fn rx(&mut self, qubit: usize, theta: f64) -> &mut Self {
+        self.push("Rx", Outer2::rx2(theta), &[qubit])
+    }
+
+    /// Rotation around Y axis
+    pub fn ry(&mut self, qubit: usize, theta: f64) -> &mut Self {
+        self.push("Ry", Outer2::ry2(theta), &[qubit])
+    }
+
+    /// Rotation around Z axis
+    pub fn rz(&mut self, qubit: usize, theta: f64) -> &mut Self {
+        self.push("Rz", Outer2::rz2(theta), &[qubit])
+    }
+
+    /// Generic single qubit rotation with Euler angles
+    pub fn u3(&mut self, qubit: usize, theta: f64, phi: f64, lambda: f64) -> &mut Self {
+        self.push("U3", Outer2::u3(theta, phi, lambda), &[qubit])
+    }
+
+    /// CNOT gate
+    pub fn cnot(&mut self, control: usize, target: usize) -> &mut Self {
+        self.push("CNOT", Outer4::cnot(), &[control, target])
+    }
+
+    /// Controlled Y gate
+    pub fn cy(&mut self, control: usize, target: usize) -> &mut Self {
+        self.push("CY", Outer4::cy(), &[control, target])
+    }
+
+    /// Controlled Z gate
+    pub fn cz(&mut self, control: usize, target: usize) -> &mut Self {
+        self.push("CZ", Outer4::cz(), &[control, target])
+    }
+
+    /// Controlled phase gate
+    pub fn cphase(&mut self, control: usize, target: usize, phi: f64) -> &mut Self {
+        self.push("CP", Outer4::cphase(phi), &[control, target])
+    }
+
+    /// SWAP gate
+    pub fn swap(&mut self, a: usize, b: usize) -> &mut Self {
+        self.push("SWAP", Outer4::swap(), &[a, b])
+    }
+
+    /// iSWAP gate
+    pub fn iswap(&mut self, a: usize, b: usize) -> &mut Self {
+        self.push("iSWAP", Outer4::iswap(), &[a, b])
+    }
+
+    /// Toffoli (CCNOT) gate
+    pub fn toffoli(&mut self, control1: usize, control2: usize, target: usize) -> &mut Self {
+        self.push("CCNOT", Outer::<U8>::toffoli(), &[control1, control2, target])
+    }
+
+    /// Fredkin (CSWAP) gate
+    pub fn fredkin(&mut self, control: usize, a: usize, b: usize) -> &mut Self {
+        self.push("CSWAP", Outer::<U8>::fredkin(), &[control, a, b])
+    }
+
+    /// Number of gates in the circuit
+    pub fn gate_count(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Number of layers of gates, where gates acting on disjoint qubits share a layer
+    pub fn depth(&self) -> usize {
+        let mut layers = vec![0; self.n_qubits];
+        for instruction in &self.instructions {
+            let layer = instruction.qubits.iter().map(|&q| layers[q]).max().unwrap_or(0) + 1;
+            for &q in &instruction.qubits {
+                layers[q] = layer;
+            }
+        }
+        layers.into_iter().max().unwrap_or(0)
+    }
+
+    /// Circuit undoing this one: gates are daggered and applied in reverse order
+    pub fn inverse(&self) -> Circuit {
+        Circuit {
+            n_qubits: self.n_qubits,
+            instructions: self.instructions.iter().rev().map(|instruction| Instruction {
+                name: if instruction.name.ends_with('†') {
+                    instruction.name.trim_end_matches('†').to_owned()
+                } else {
+                    format!("{}†", instruction.name)
+                },
+                gate: instruction.gate.dagger(),
+                qubits: instruction.qubits.clone(),
+                builtin: instruction.builtin,
+            }).collect(),
+        }
+    }
+
+    // Dimension of the register, 2^n_qubits
+    fn dim(&self) -> Result<usize, &'static str> {
+        if self.n_qubits >= usize::BITS as usize {
+            return Err("Register is too large to be simulated");
+        }
+        Ok(1 << self.n_qubits)
+    }
+
+    fn run_in_place(&self, amplitudes: &mut [Complex]) -> Result<(), &'static str> {
+        if amplitudes.len() != self.dim()? {
+            return Err("State dimension does not match the circuit register");
+        }
+        for instruction in &self.instructions {
+            linalg::apply_gate(amplitudes, instruction.gate.0.as_slice(), &instruction.qubits)?;
+        }
+        Ok(())
+    }
+
+    /// Final state of the register after running the circuit on `state`
+    pub fn run<D: DimName>(&self, state: &Ket<D>) -> Result<Ket<D>, &'static str>
+        where DefaultAllocator: Allocator<Complex, D>
+    {
+        let mut result = state.clone();
+        self.run_in_place(result.0.as_mut_slice())?;
+        Ok(result)
+    }
+
+    /// Final state of the register after running the circuit on the runtime-sized `state`
+    pub fn run_dynamic(&self, state: &DKet) -> Result<DKet, &'static str> {
+        let mut result = state.clone();
+        self.run_in_place(result.0.as_mut_slice())?;
+        Ok(result)
+    }
+
+    /// Composed operator of the whole circuit
+    pub fn unitary<D: DimName>(&self) -> Result<Outer<D>, &'static str>
+        where DefaultAllocator: Allocator<Complex, D, D>
+    {
+        if D::name().value() != self.dim()? {
+            return Err("Operator dimension does not match the circuit register");
+        }
+        Outer::try_from(self.unitary_dynamic()?)
+    }
+
+    /// Composed runtime-sized operator of the whole circuit
+    pub fn unitary_dynamic(&self) -> Result<DOuter, &'static str> {
+        let dim = self.dim()?;
+        let mut result = DOuter::identity(dim);
+        // column-major storage, so every chunk is a column
+        for column in result.0.as_mut_slice().chunks_mut(dim) {
+            self.run_in_place(column).expect("column has the register dimension");
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,207 @@
+use std::convert::TryFrom;
+
+use nalgebra::{DefaultAllocator, DimName, MatrixMN, U1, VectorN};
+use nalgebra::allocator::Allocator;
+use rand::Rng;
+
+use {dirac, linalg, measure, Basis, Ket, Outer, Complex, TOLERANCE};
+
+/// Density matrix of a (possibly mixed) state.
+///
+/// Always hermitian, positive semi-definite and of unit trace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensityMatrix<D: DimName>(pub(crate) MatrixMN<Complex, D, D>)
+    where DefaultAllocator: Allocator<Complex, D, D>;
+
+impl<D: DimName> DensityMatrix<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+{
+    /// Density matrix from the operator, which should be hermitian, positive semi-definite
+    /// and of unit trace, e.g. `Ket * Bra` of the normalized ket.
+    pub fn new(outer: Outer<D>) -> Result<DensityMatrix<D>, &'static str> {
+        let matrix = linalg::to_dynamic(&outer.0);
+
+        if !linalg::is_hermitian(&matrix) {
+            return Err("Density matrix should be hermitian");
+        }
+        if (matrix.trace() - Complex::from(1.0)).norm() > TOLERANCE {
+            return Err("Density matrix should be of unit trace");
+        }
+        let (eigenvalues, _) = linalg::hermitian_eigen(matrix);
+        if eigenvalues.iter().any(|&v| v < -TOLERANCE) {
+            return Err("Density matrix should be positive semi-definite");
+        }
+
+        Ok(DensityMatrix(outer.0))
+    }
+
+    /// Density matrix of the pure state, |ψ><ψ| (ket is normalized)
+    pub fn pure(ket: &Ket<D>) -> DensityMatrix<D> {
+        let normalized = ket.0.normalize();
+        DensityMatrix(&normalized * normalized.adjoint())
+    }
+
+    /// Convex mixture `Σ p_i |ψ_i><ψ_i|` of pure states (kets are normalized)
+    ///
+    /// Weights should be non-negative and sum up to 1.
+    pub fn mixture(states: &[(f64, Ket<D>)]) -> Result<DensityMatrix<D>, &'static str> {
+        if states.iter().any(|&(p, _)| p < 0.0) {
+            return Err("Mixture weights should be non-negative");
+        }
+        if (states.iter().map(|&(p, _)| p).sum::<f64>() - 1.0).abs() > TOLERANCE {
+            return Err("Mixture weights should sum up to 1");
+        }
+
+        let mut matrix = MatrixMN::zeros_generic(D::name(), D::name());
+        for &(p, ref ket) in states {
+            matrix += DensityMatrix::pure(ket).0 * Complex::from(p);
+        }
+        Ok(DensityMatrix(matrix))
+    }
+
+    /// Maximally mixed state, I/d
+    pub fn maximally_mixed() -> DensityMatrix<D> {
+        let dim = D::name().value();
+        DensityMatrix(MatrixMN::identity_generic(D::name(), D::name()) / Complex::from(dim as f64))
+    }
+
+    /// Deconstruct the density matrix returning it as an operator.
+    pub fn into_outer(self) -> Outer<D> {
+        Outer(self.0)
+    }
+
+    /// Purity of the state, Tr(ρ²), equal to 1 only for pure states
+    pub fn purity(&self) -> f64 {
+        (&self.0 * &self.0).trace().re
+    }
+
+    /// Whether the state is pure
+    pub fn is_pure(&self) -> bool {
+        (self.purity() - 1.0).abs() < TOLERANCE
+    }
+
+    /// Expectation value of the observable, Tr(ρA)
+    pub fn expectation(&self, observable: &Outer<D>) -> Complex {
+        (&self.0 * &observable.0).trace()
+    }
+
+    /// State after the unitary evolution, UρU†
+    pub fn evolve(&self, unitary: &Outer<D>) -> DensityMatrix<D> {
+        DensityMatrix(&unitary.0 * &self.0 * unitary.0.adjoint())
+    }
+
+    /// Probabilities of all outcomes of the measurement in the computational basis
+    pub fn probabilities(&self) -> Vec<f64> {
+        self.0.diagonal().iter().map(|v| v.re).collect()
+    }
+
+    /// Probabilities of all outcomes of the measurement in the given basis
+    pub fn probabilities_in(&self, basis: &Basis<D>) -> Vec<f64> {
+        basis.kets().iter().map(|b| b.0.dotc(&(&self.0 * &b.0)).re).collect()
+    }
+
+    /// Probability of the outcome and the post-measurement state for the projector `P`,
+    /// i.e. `Tr(PρP)` and `PρP / Tr(PρP)`.
+    ///
+    /// Fails if the outcome is impossible.
+    pub fn project(&self, projector: &Outer<D>) -> Result<(f64, DensityMatrix<D>), &'static str> {
+        let projected = &projector.0 * &self.0 * &projector.0;
+        let probability = projected.trace().re;
+        if probability < TOLERANCE {
+            return Err("Outcome of the projector has zero probability");
+        }
+        Ok((probability, DensityMatrix(projected / Complex::from(probability))))
+    }
+
+    /// Measure the state in the computational basis
+    ///
+    /// Returns the outcome (index of the basis state) and the post-measurement state.
+    pub fn measure<R: Rng>(&self, rng: &mut R) -> (usize, DensityMatrix<D>) {
+        self.measure_in(&Basis::computational(), rng)
+    }
+
+    /// Measure the state in the given basis
+    ///
+    /// Returns the outcome (index of the basis ket) and the post-measurement state.
+    pub fn measure_in<R: Rng>(&self, basis: &Basis<D>, rng: &mut R) -> (usize, DensityMatrix<D>) {
+        let outcome = measure::pick(&self.probabilities_in(basis), rng);
+        (outcome, DensityMatrix::pure(&basis.kets()[outcome]))
+    }
+
+    /// Reduced state of the qubits `keep`, tracing out the rest of the register
+    pub fn partial_trace<K: DimName>(&self, keep: &[usize]) -> Result<DensityMatrix<K>, &'static str>
+        where DefaultAllocator: Allocator<Complex, K, K>
+    {
+        Outer(self.0.clone()).partial_trace(keep).map(|reduced| DensityMatrix(reduced.0))
+    }
+
+    /// Reduced state of the subsystems `keep`, for the register composed of subsystems of
+    /// `dims` dimensions
+    pub fn partial_trace_subsystems<K: DimName>(&self, dims: &[usize], keep: &[usize]) -> Result<DensityMatrix<K>, &'static str>
+        where DefaultAllocator: Allocator<Complex, K, K>
+    {
+        Outer(self.0.clone()).partial_trace_subsystems(dims, keep).map(|reduced| DensityMatrix(reduced.0))
+    }
+
+    /// Ket of the pure state, with the global phase chosen to make the first non-zero
+    /// amplitude real and positive
+    pub fn to_ket(&self) -> Result<Ket<D>, &'static str> {
+        if !self.is_pure() {
+            return Err("Only pure state can be converted to ket");
+        }
+
+        let (eigenvalues, eigenvectors) = linalg::hermitian_eigen(linalg::to_dynamic(&self.0));
+        let principal = eigenvalues.iter().enumerate()
+            .fold(0, |best, (i, v)| if *v > eigenvalues[best] { i } else { best });
+
+        let column = eigenvectors.column(principal);
+        let phase = column.iter().find(|v| v.norm() > TOLERANCE).map(|v| v.conj() / v.norm()).unwrap_or_else(|| 1.0.into());
+
+        Ok(Ket(VectorN::from_iterator_generic(D::name(), U1, column.iter().map(|v| v * phase))))
+    }
+}
+
+impl<D: DimName> Ket<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+{
+    /// Reduced (generally mixed) state of the `qubits`, tracing out the rest of the register
+    pub fn reduced_state<K: DimName>(&self, qubits: &[usize]) -> Result<DensityMatrix<K>, &'static str>
+        where DefaultAllocator: Allocator<Complex, K, K>
+    {
+        DensityMatrix::pure(self).partial_trace(qubits)
+    }
+
+    /// Reduced (generally mixed) state of the subsystems `keep`, for the register composed of
+    /// subsystems of `dims` dimensions
+    pub fn reduced_state_subsystems<K: DimName>(&self, dims: &[usize], keep: &[usize]) -> Result<DensityMatrix<K>, &'static str>
+        where DefaultAllocator: Allocator<Complex, K, K>
+    {
+        DensityMatrix::pure(self).partial_trace_subsystems(dims, keep)
+    }
+}
+
+impl<D: DimName> From<Ket<D>> for DensityMatrix<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+{
+    fn from(v: Ket<D>) -> Self {
+        DensityMatrix::pure(&v)
+    }
+}
+
+impl<D: DimName> TryFrom<Outer<D>> for DensityMatrix<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+{
+    type Error = &'static str;
+
+    fn try_from(v: Outer<D>) -> Result<Self, Self::Error> {
+        DensityMatrix::new(v)
+    }
+}
+
+impl<D: DimName> ::std::fmt::Display for DensityMatrix<D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(&self.to_dirac(&dirac::formatter_options(f)))
+    }
+}
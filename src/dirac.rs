@@ -0,0 +1,252 @@
+use std::f64::consts::PI;
+
+use nalgebra::{DefaultAllocator, DimName, U1};
+use nalgebra::allocator::Allocator;
+
+use {measure, Bra, DBra, DKet, DOuter, DensityMatrix, Ket, Outer, Complex};
+
+/// Target of the Dirac notation output: plain text or LaTeX (`\ket{}`, `\bra{}`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Notation {
+    Text,
+    Latex,
+}
+
+/// Labels of the basis states in Dirac notation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Labels {
+    /// Bit strings for qubit registers (`|01⟩`), integers for other dimensions
+    Qubits,
+    /// Basis state indices (`|1⟩`)
+    Integers,
+}
+
+/// Options of the Dirac notation output, e.g. `0.707|00⟩ + 0.707|11⟩`.
+///
+/// Default is 3 digits after the decimal point, terms rounding to zero dropped, rectangular
+/// complex numbers, qubit labels and Unicode brackets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiracFormat {
+    precision: usize,
+    drop_zeros: bool,
+    polar: bool,
+    labels: Labels,
+    unicode: bool,
+}
+
+impl Default for DiracFormat {
+    fn default() -> Self {
+        DiracFormat { precision: 3, drop_zeros: true, polar: false, labels: Labels::Qubits, unicode: true }
+    }
+}
+
+impl DiracFormat {
+    /// Default options
+    pub fn new() -> DiracFormat {
+        DiracFormat::default()
+    }
+
+    /// Digits after the decimal point of amplitudes (and phases)
+    pub fn precision(mut self, digits: usize) -> DiracFormat {
+        self.precision = digits;
+        self
+    }
+
+    /// Whether to drop terms with amplitudes rounding to zero
+    pub fn drop_zeros(mut self, drop: bool) -> DiracFormat {
+        self.drop_zeros = drop;
+        self
+    }
+
+    /// Whether to print complex amplitudes in polar form, `0.707e^(1.571i)`, instead of
+    /// rectangular one, `(0.500+0.500i)`
+    pub fn polar(mut self, polar: bool) -> DiracFormat {
+        self.polar = polar;
+        self
+    }
+
+    /// Labels of the basis states
+    pub fn labels(mut self, labels: Labels) -> DiracFormat {
+        self.labels = labels;
+        self
+    }
+
+    /// Whether to use Unicode angle brackets `⟨⟩` instead of ASCII `<>`
+    pub fn unicode(mut self, unicode: bool) -> DiracFormat {
+        self.unicode = unicode;
+        self
+    }
+
+    fn label(&self, index: usize, dim: usize) -> String {
+        match self.labels {
+            Labels::Qubits => measure::basis_label(index, dim),
+            Labels::Integers => format!("{}", index),
+        }
+    }
+
+    fn ket_label(&self, index: usize, dim: usize, notation: Notation) -> String {
+        match notation {
+            Notation::Text => format!("|{}{}", self.label(index, dim), if self.unicode { "⟩" } else { ">" }),
+            Notation::Latex => format!("\\ket{{{}}}", self.label(index, dim)),
+        }
+    }
+
+    fn bra_label(&self, index: usize, dim: usize, notation: Notation) -> String {
+        match notation {
+            Notation::Text => format!("{}{}|", if self.unicode { "⟨" } else { "<" }, self.label(index, dim)),
+            Notation::Latex => format!("\\bra{{{}}}", self.label(index, dim)),
+        }
+    }
+
+    pub(crate) fn is_zero(&self, v: f64) -> bool {
+        v.abs() < 0.5 * 10f64.powi(-(self.precision as i32))
+    }
+
+    /// Complex number in the chosen (polar or rectangular) form
+    pub(crate) fn number(&self, c: Complex, notation: Notation) -> String {
+        let p = self.precision;
+        if self.polar {
+            let (r, phase) = c.to_polar();
+            if self.is_zero(phase) || self.is_zero(r) {
+                format!("{:.*}", p, r)
+            } else if self.is_zero(phase.abs() - PI) {
+                format!("{:.*}", p, -r)
+            } else {
+                match notation {
+                    Notation::Text => format!("{:.*}e^({:.*}i)", p, r, p, phase),
+                    Notation::Latex => format!("{:.*}e^{{{:.*}i}}", p, r, p, phase),
+                }
+            }
+        } else if self.is_zero(c.im) {
+            format!("{:.*}", p, c.re)
+        } else if self.is_zero(c.re) {
+            format!("{:.*}i", p, c.im)
+        } else {
+            format!("{:.*}{:+.*}i", p, c.re, p, c.im)
+        }
+    }
+
+    // Coefficient of the term, with the unit magnitude omitted for ±1 and ±i
+    fn coefficient(&self, c: Complex, notation: Notation) -> String {
+        let text = self.number(c, notation);
+        if !self.polar && !self.is_zero(c.re) && !self.is_zero(c.im) {
+            return format!("({})", text);
+        }
+
+        let one = format!("{:.*}", self.precision, 1.0);
+        let (sign, magnitude) = if let Some(stripped) = text.strip_prefix('-') { ("-", stripped) } else { ("", &text[..]) };
+        if magnitude == one {
+            sign.to_owned()
+        } else if magnitude.strip_suffix('i') == Some(&one[..]) {
+            format!("{}i", sign)
+        } else {
+            text
+        }
+    }
+
+    // Sum of the terms `c label`, "0" when no terms are left
+    fn sum<I: Iterator<Item=(Complex, String)>>(&self, terms: I, notation: Notation) -> String {
+        let mut result = String::new();
+        for (c, label) in terms {
+            if self.drop_zeros && self.is_zero(c.norm()) {
+                continue;
+            }
+            let coefficient = self.coefficient(c, notation);
+            if result.is_empty() {
+                result.push_str(&coefficient);
+            } else if let Some(negated) = coefficient.strip_prefix('-') {
+                result.push_str(" - ");
+                result.push_str(negated);
+            } else {
+                result.push_str(" + ");
+                result.push_str(&coefficient);
+            }
+            result.push_str(&label);
+        }
+        if result.is_empty() {
+            result.push('0');
+        }
+        result
+    }
+
+    pub(crate) fn ket(&self, amplitudes: &[Complex], notation: Notation) -> String {
+        let dim = amplitudes.len();
+        self.sum(amplitudes.iter().enumerate().map(|(i, &c)| (c, self.ket_label(i, dim, notation))), notation)
+    }
+
+    // Bra stores components of its dual ket, so coefficients are conjugated
+    pub(crate) fn bra(&self, amplitudes: &[Complex], notation: Notation) -> String {
+        let dim = amplitudes.len();
+        self.sum(amplitudes.iter().enumerate().map(|(i, c)| (c.conj(), self.bra_label(i, dim, notation))), notation)
+    }
+
+    // Column-major square matrix as a sum of |i⟩⟨j|
+    pub(crate) fn operator(&self, matrix: &[Complex], dim: usize, notation: Notation) -> String {
+        self.sum((0..dim * dim).map(|k| {
+            let (i, j) = (k / dim, k % dim);
+            (matrix[j * dim + i], format!("{}{}", self.ket_label(i, dim, notation), self.bra_label(j, dim, notation)))
+        }), notation)
+    }
+}
+
+impl<D: DimName> Ket<D>
+    where DefaultAllocator: Allocator<Complex, D>
+{
+    /// Ket in Dirac notation, e.g. `0.707|00⟩ + 0.707|11⟩`
+    pub fn to_dirac(&self, format: &DiracFormat) -> String {
+        format.ket(self.0.as_slice(), Notation::Text)
+    }
+}
+
+impl<D: DimName> Bra<D>
+    where DefaultAllocator: Allocator<Complex, U1, D>
+{
+    /// Bra in Dirac notation, e.g. `0.707⟨00| + 0.707⟨11|`
+    pub fn to_dirac(&self, format: &DiracFormat) -> String {
+        format.bra(self.0.as_slice(), Notation::Text)
+    }
+}
+
+impl<D: DimName> Outer<D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    /// Operator in Dirac notation as a sum of `|i⟩⟨j|` terms
+    pub fn to_dirac(&self, format: &DiracFormat) -> String {
+        format.operator(self.0.as_slice(), D::name().value(), Notation::Text)
+    }
+}
+
+impl<D: DimName> DensityMatrix<D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    /// Density matrix in Dirac notation as a sum of `|i⟩⟨j|` terms
+    pub fn to_dirac(&self, format: &DiracFormat) -> String {
+        format.operator(self.0.as_slice(), D::name().value(), Notation::Text)
+    }
+}
+
+impl DKet {
+    /// Ket in Dirac notation, e.g. `0.707|00⟩ + 0.707|11⟩`
+    pub fn to_dirac(&self, format: &DiracFormat) -> String {
+        format.ket(self.0.as_slice(), Notation::Text)
+    }
+}
+
+impl DBra {
+    /// Bra in Dirac notation, e.g. `0.707⟨00| + 0.707⟨11|`
+    pub fn to_dirac(&self, format: &DiracFormat) -> String {
+        format.bra(self.0.as_slice(), Notation::Text)
+    }
+}
+
+impl DOuter {
+    /// Operator in Dirac notation as a sum of `|i⟩⟨j|` terms
+    pub fn to_dirac(&self, format: &DiracFormat) -> String {
+        format.operator(self.0.as_slice(), self.dim(), Notation::Text)
+    }
+}
+
+/// Default format with the precision of the formatter, if any (`{:.2}`)
+pub(crate) fn formatter_options(f: &::std::fmt::Formatter) -> DiracFormat {
+    f.precision().map_or_else(DiracFormat::default, |p| DiracFormat::default().precision(p))
+}
@@ -0,0 +1,392 @@
+use std::convert::TryFrom;
+
+use nalgebra::{DefaultAllocator, DMatrix, DVector, DimName, MatrixMN, RowDVector, U1, VectorN, RowVectorN};
+use nalgebra::allocator::Allocator;
+
+use {dirac, linalg, Bra, Ket, Outer, Complex};
+
+/// Ket of dimension known only at runtime. You can multiply it by bra to an outer product (or operator).
+///
+/// # Panics
+///
+/// Arithmetic operators (`+`, `-`, `*`) panic if the dimensions of the operands do not match,
+/// `TryAdd` and `TryMul` return an error instead. The same holds for `DBra` and `DOuter`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DKet(pub(crate) DVector<Complex>);
+
+/// Bra of dimension known only at runtime. You can multiply it by ket to get an inner product (scalar).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DBra(pub(crate) RowDVector<Complex>);
+
+/// Outer product (or linear operator) of dimension known only at runtime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DOuter(pub(crate) DMatrix<Complex>);
+
+impl DKet {
+    /// Basis ket |index> of the given dimension
+    pub fn basis(dim: usize, index: usize) -> Result<DKet, &'static str> {
+        if index >= dim {
+            return Err("Basis index is out of the dimension range");
+        }
+        Ok(DKet(DVector::from_fn(dim, |i, _| if i == index { 1.0.into() } else { 0.0.into() })))
+    }
+
+    /// Dimension of the ket
+    pub fn dim(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Kronecker (tensor) product of two kets
+    pub fn tensor(&self, other: &DKet) -> DKet {
+        DKet(self.0.kronecker(&other.0))
+    }
+
+    /// Apply a gate to the chosen qubits of the register in place.
+    ///
+    /// Same conventions as `Ket::apply_gate`.
+    pub fn apply_gate(&mut self, gate: &DOuter, qubits: &[usize]) -> Result<(), &'static str> {
+        linalg::apply_gate(self.0.as_mut_slice(), gate.0.as_slice(), qubits)
+    }
+
+    /// Deconstruct the ket returning the inner vector.
+    pub fn into_vector(self) -> DVector<Complex> {
+        self.0
+    }
+}
+
+impl DBra {
+    /// Basis bra <index| of the given dimension
+    pub fn basis(dim: usize, index: usize) -> Result<DBra, &'static str> {
+        DKet::basis(dim, index).map(DBra::from)
+    }
+
+    /// Dimension of the bra
+    pub fn dim(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Kronecker (tensor) product of two bras
+    pub fn tensor(&self, other: &DBra) -> DBra {
+        DBra(self.0.kronecker(&other.0))
+    }
+}
+
+impl DOuter {
+    /// Identity operator of the given dimension
+    pub fn identity(dim: usize) -> DOuter {
+        DOuter(DMatrix::identity(dim, dim))
+    }
+
+    /// Dimension of the operator
+    pub fn dim(&self) -> usize {
+        self.0.nrows()
+    }
+
+    /// Deconstruct the outer product returning the inner matrix.
+    pub fn into_matrix(self) -> DMatrix<Complex> {
+        self.0
+    }
+
+    /// Kronecker (tensor) product of two operators
+    pub fn tensor(&self, other: &DOuter) -> DOuter {
+        DOuter(self.0.kronecker(&other.0))
+    }
+
+    /// Hermitian conjugate (adjoint) operator, A†
+    pub fn dagger(&self) -> DOuter {
+        DOuter(self.0.adjoint())
+    }
+
+    /// Trace of the operator, Tr(A)
+    pub fn trace(&self) -> Complex {
+        self.0.trace()
+    }
+
+    /// Determinant of the operator, det A
+    pub fn determinant(&self) -> Complex {
+        self.0.determinant()
+    }
+
+    /// Integer power of the operator, A^n (A^0 is identity)
+    pub fn pow(&self, n: u32) -> DOuter {
+        let mut result = DOuter::identity(self.dim());
+        let mut base = self.clone();
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            n >>= 1;
+        }
+        result
+    }
+
+    /// Operator exponent, e^A (operator should be normal)
+    pub fn exp(&self) -> DOuter {
+        DOuter(linalg::map_normal(self.0.clone(), |v| v.exp()))
+    }
+
+    /// Principal square root of the operator, √A (operator should be normal)
+    pub fn sqrt(&self) -> DOuter {
+        DOuter(linalg::map_normal(self.0.clone(), |v| v.sqrt()))
+    }
+
+    /// Principal logarithm of the operator, log A (operator should be normal and non-singular)
+    pub fn log(&self) -> DOuter {
+        DOuter(linalg::map_normal(self.0.clone(), |v| v.ln()))
+    }
+
+    /// Embed the operator acting on `qubits` into the register of `dim` dimension.
+    ///
+    /// Same conventions as `Outer::on_qubits`.
+    pub fn on_qubits(&self, qubits: &[usize], dim: usize) -> Result<DOuter, &'static str> {
+        let mut result = DMatrix::zeros(dim, dim);
+        let mut column = vec![Complex::from(0.0); dim];
+
+        for j in 0..dim {
+            for (i, c) in column.iter_mut().enumerate() {
+                *c = if i == j { 1.0.into() } else { 0.0.into() };
+            }
+            linalg::apply_gate(&mut column, self.0.as_slice(), qubits)?;
+            result.column_mut(j).copy_from_slice(&column);
+        }
+
+        Ok(DOuter(result))
+    }
+}
+
+/// Addition checking the dimensions of the operands.
+pub trait TryAdd<Rhs = Self> {
+    /// Sum type
+    type Output;
+
+    /// Sum of the values, or an error if the dimensions do not match
+    fn try_add(self, other: Rhs) -> Result<Self::Output, &'static str>;
+
+    /// Difference of the values, or an error if the dimensions do not match
+    fn try_sub(self, other: Rhs) -> Result<Self::Output, &'static str>;
+}
+
+/// Multiplication checking the dimensions of the operands.
+pub trait TryMul<Rhs = Self> {
+    /// Product type
+    type Output;
+
+    /// Product of the values, or an error if the dimensions do not match
+    fn try_mul(self, other: Rhs) -> Result<Self::Output, &'static str>;
+}
+
+macro_rules! impl_try_add {
+    ($T:ty) => {
+        impl<'a, 'b> TryAdd<&'b $T> for &'a $T {
+            type Output = $T;
+
+            fn try_add(self, other: &'b $T) -> Result<$T, &'static str> {
+                if self.dim() != other.dim() {
+                    return Err("Dimensions do not match");
+                }
+                Ok(self + other)
+            }
+
+            fn try_sub(self, other: &'b $T) -> Result<$T, &'static str> {
+                if self.dim() != other.dim() {
+                    return Err("Dimensions do not match");
+                }
+                Ok(self - other)
+            }
+        }
+    };
+}
+
+macro_rules! impl_try_mul {
+    ($Lhs:ty, $Rhs:ty => $Out:ty) => {
+        impl<'a, 'b> TryMul<&'b $Rhs> for &'a $Lhs {
+            type Output = $Out;
+
+            fn try_mul(self, other: &'b $Rhs) -> Result<$Out, &'static str> {
+                if self.dim() != other.dim() {
+                    return Err("Dimensions do not match");
+                }
+                Ok(self * other)
+            }
+        }
+    };
+}
+
+impl_try_add!(DKet);
+impl_try_add!(DBra);
+impl_try_add!(DOuter);
+
+impl_try_mul!(DKet, DBra => DOuter);
+impl_try_mul!(DBra, DKet => Complex);
+impl_try_mul!(DBra, DOuter => DBra);
+impl_try_mul!(DOuter, DKet => DKet);
+impl_try_mul!(DOuter, DOuter => DOuter);
+
+impl_binop!(
+    [] Mul, mul for DKet, DBra => DOuter
+    where []
+    |ket, bra| DOuter(&ket.0 * bra.0.conjugate())
+);
+
+impl_binop!(
+    [] Mul, mul for DBra, DKet => Complex
+    where []
+    |bra, ket| (bra.0.conjugate() * &ket.0)[0]
+);
+
+impl_binop!(
+    [] Mul, mul for DBra, DOuter => DBra
+    where []
+    |bra, outer| DBra(&bra.0 * outer.0.conjugate())
+);
+
+impl_binop!(
+    [] Mul, mul for DOuter, DKet => DKet
+    where []
+    |outer, ket| DKet(&outer.0 * &ket.0)
+);
+
+impl_binop!(
+    [] Mul, mul for DOuter, DOuter => DOuter
+    where []
+    |a, b| DOuter(&a.0 * &b.0)
+);
+
+impl_binop!([] Add, add for DKet, DKet => DKet where [] |a, b| DKet(&a.0 + &b.0));
+impl_binop!([] Sub, sub for DKet, DKet => DKet where [] |a, b| DKet(&a.0 - &b.0));
+impl_binop!([] Add, add for DBra, DBra => DBra where [] |a, b| DBra(&a.0 + &b.0));
+impl_binop!([] Sub, sub for DBra, DBra => DBra where [] |a, b| DBra(&a.0 - &b.0));
+impl_binop!([] Add, add for DOuter, DOuter => DOuter where [] |a, b| DOuter(&a.0 + &b.0));
+impl_binop!([] Sub, sub for DOuter, DOuter => DOuter where [] |a, b| DOuter(&a.0 - &b.0));
+
+impl_neg!([] for DKet where [] |a| DKet(-&a.0));
+impl_neg!([] for DBra where [] |a| DBra(-&a.0));
+impl_neg!([] for DOuter where [] |a| DOuter(-&a.0));
+
+impl_scalar_op!([] Mul, mul for DKet, Complex => DKet where [] |ket, scalar| DKet(&ket.0 * scalar));
+impl_scalar_op!([] Div, div for DKet, Complex => DKet where [] |ket, scalar| DKet(&ket.0 / scalar));
+// Scaling is applied to the bra itself, i.e. to the conjugate of the stored components.
+impl_scalar_op!([] Mul, mul for DBra, Complex => DBra where [] |bra, scalar| DBra(&bra.0 * scalar.conj()));
+impl_scalar_op!([] Div, div for DBra, Complex => DBra where [] |bra, scalar| DBra(&bra.0 / scalar.conj()));
+impl_scalar_op!([] Mul, mul for DOuter, f64 => DOuter where [] |outer, scalar| DOuter(&outer.0 * Complex::new(scalar, 0.0)));
+impl_scalar_op!([] Mul, mul for DOuter, Complex => DOuter where [] |outer, scalar| DOuter(&outer.0 * scalar));
+impl_scalar_op!([] Div, div for DOuter, f64 => DOuter where [] |outer, scalar| DOuter(&outer.0 / Complex::new(scalar, 0.0)));
+impl_scalar_op!([] Div, div for DOuter, Complex => DOuter where [] |outer, scalar| DOuter(&outer.0 / scalar));
+
+impl From<DBra> for DKet {
+    fn from(v: DBra) -> Self {
+        DKet(v.0.transpose())
+    }
+}
+
+impl From<DKet> for DBra {
+    fn from(v: DKet) -> Self {
+        DBra(v.0.transpose())
+    }
+}
+
+impl From<DVector<Complex>> for DKet {
+    fn from(v: DVector<Complex>) -> Self {
+        DKet(v)
+    }
+}
+
+impl From<RowDVector<Complex>> for DBra {
+    fn from(v: RowDVector<Complex>) -> Self {
+        DBra(v)
+    }
+}
+
+impl TryFrom<DMatrix<Complex>> for DOuter {
+    type Error = &'static str;
+
+    fn try_from(v: DMatrix<Complex>) -> Result<Self, Self::Error> {
+        if v.nrows() != v.ncols() {
+            return Err("Operator matrix should be square");
+        }
+        Ok(DOuter(v))
+    }
+}
+
+impl<D: DimName> From<Ket<D>> for DKet
+    where DefaultAllocator: Allocator<Complex, D>
+{
+    fn from(v: Ket<D>) -> Self {
+        DKet(DVector::from_iterator(D::name().value(), v.0.iter().cloned()))
+    }
+}
+
+impl<D: DimName> From<Bra<D>> for DBra
+    where DefaultAllocator: Allocator<Complex, U1, D>
+{
+    fn from(v: Bra<D>) -> Self {
+        DBra(RowDVector::from_iterator(D::name().value(), v.0.iter().cloned()))
+    }
+}
+
+impl<D: DimName> From<Outer<D>> for DOuter
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    fn from(v: Outer<D>) -> Self {
+        DOuter(linalg::to_dynamic(&v.0))
+    }
+}
+
+impl<D: DimName> TryFrom<DKet> for Ket<D>
+    where DefaultAllocator: Allocator<Complex, D>
+{
+    type Error = &'static str;
+
+    fn try_from(v: DKet) -> Result<Self, Self::Error> {
+        if v.dim() != D::name().value() {
+            return Err("Dynamic ket dimension does not match the static one");
+        }
+        Ok(Ket(VectorN::from_iterator_generic(D::name(), U1, v.0.iter().cloned())))
+    }
+}
+
+impl<D: DimName> TryFrom<DBra> for Bra<D>
+    where DefaultAllocator: Allocator<Complex, U1, D>
+{
+    type Error = &'static str;
+
+    fn try_from(v: DBra) -> Result<Self, Self::Error> {
+        if v.dim() != D::name().value() {
+            return Err("Dynamic bra dimension does not match the static one");
+        }
+        Ok(Bra(RowVectorN::from_iterator_generic(U1, D::name(), v.0.iter().cloned())))
+    }
+}
+
+impl<D: DimName> TryFrom<DOuter> for Outer<D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    type Error = &'static str;
+
+    fn try_from(v: DOuter) -> Result<Self, Self::Error> {
+        if v.dim() != D::name().value() {
+            return Err("Dynamic outer dimension does not match the static one");
+        }
+        Ok(Outer(MatrixMN::from_iterator_generic(D::name(), D::name(), v.0.iter().cloned())))
+    }
+}
+
+impl ::std::fmt::Display for DKet {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(&self.to_dirac(&dirac::formatter_options(f)))
+    }
+}
+
+impl ::std::fmt::Display for DBra {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(&self.to_dirac(&dirac::formatter_options(f)))
+    }
+}
+
+impl ::std::fmt::Display for DOuter {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(&self.to_dirac(&dirac::formatter_options(f)))
+    }
+}
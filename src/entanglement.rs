@@ -0,0 +1,145 @@
+use nalgebra::{DefaultAllocator, DMatrix, DVector, DimName, U1, U4};
+use nalgebra::allocator::Allocator;
+
+use {linalg, DensityMatrix, DKet, Ket, Outer, Outer2, Complex, TOLERANCE};
+
+/// Schmidt decomposition of a bipartite pure state, `|ψ> = Σ c_k |a_k> ⊗ |b_k>`.
+///
+/// Only terms with non-zero coefficients are kept, in the order of decreasing coefficients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchmidtDecomposition {
+    /// Schmidt coefficients `c_k`, positive and decreasing
+    pub coefficients: Vec<f64>,
+    /// Orthonormal kets `|a_k>` of the first subsystem
+    pub first: Vec<DKet>,
+    /// Orthonormal kets `|b_k>` of the second subsystem
+    pub second: Vec<DKet>,
+}
+
+impl SchmidtDecomposition {
+    /// Number of non-zero terms, 1 only for product states
+    pub fn rank(&self) -> usize {
+        self.coefficients.len()
+    }
+}
+
+// -Σ p log₂ p over non-zero probabilities
+fn shannon_entropy<I: IntoIterator<Item=f64>>(probabilities: I) -> f64 {
+    probabilities.into_iter()
+        .filter(|&p| p > TOLERANCE)
+        .fold(0.0, |acc, p| acc - p * p.log2())
+}
+
+impl<D: DimName> Ket<D>
+    where DefaultAllocator: Allocator<Complex, D>
+{
+    /// Schmidt decomposition of the normalized state with respect to the split into the first
+    /// subsystem of `split` dimension and the rest (e.g. `split = 2` separates the first qubit).
+    pub fn schmidt_decomposition(&self, split: usize) -> Result<SchmidtDecomposition, &'static str> {
+        let dim = D::name().value();
+        if split == 0 || dim % split != 0 {
+            return Err("Split should divide the dimension of the state");
+        }
+        let other = dim / split;
+
+        // |ψ> = Σ M_ij |i>|j>, M = U Σ V† gives |ψ> = Σ σ_k |u_k> ⊗ |v_k*>
+        let normalized = self.0.normalize();
+        let matrix = DMatrix::from_fn(split, other, |i, j| normalized[i * other + j]);
+        let (u, singular_values, v_t) = linalg::svd(matrix);
+
+        let mut order: Vec<usize> = (0..singular_values.len())
+            .filter(|&k| singular_values[k] > TOLERANCE)
+            .collect();
+        order.sort_by(|&a, &b| singular_values[b].partial_cmp(&singular_values[a]).expect("singular values are finite"));
+
+        Ok(SchmidtDecomposition {
+            coefficients: order.iter().map(|&k| singular_values[k]).collect(),
+            first: order.iter().map(|&k| DKet(DVector::from_iterator(split, u.column(k).iter().cloned()))).collect(),
+            second: order.iter().map(|&k| DKet(DVector::from_iterator(other, v_t.row(k).iter().cloned()))).collect(),
+        })
+    }
+
+    /// Entanglement entropy (in bits) of the state with respect to the split into the first
+    /// subsystem of `split` dimension and the rest
+    pub fn entanglement_entropy(&self, split: usize) -> Result<f64, &'static str> {
+        self.schmidt_decomposition(split)
+            .map(|schmidt| shannon_entropy(schmidt.coefficients.iter().map(|c| c * c)))
+    }
+}
+
+impl<D: DimName> DensityMatrix<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+{
+    /// Von Neumann entropy (in bits), `-Tr(ρ log₂ ρ)`
+    pub fn entropy(&self) -> f64 {
+        let (eigenvalues, _) = linalg::hermitian_eigen(linalg::to_dynamic(&self.0));
+        shannon_entropy(eigenvalues)
+    }
+
+    /// Partial transpose of the `qubits` of the register
+    pub fn partial_transpose(&self, qubits: &[usize]) -> Result<Outer<D>, &'static str> {
+        let n = linalg::qubit_count(D::name().value())?;
+        self.partial_transpose_subsystems(&vec![2; n], qubits)
+    }
+
+    /// Partial transpose of the `transpose` subsystems, for the register composed of subsystems
+    /// of `dims` dimensions
+    pub fn partial_transpose_subsystems(&self, dims: &[usize], transpose: &[usize]) -> Result<Outer<D>, &'static str> {
+        linalg::partial_transpose(&linalg::to_dynamic(&self.0), dims, transpose)
+            .map(|m| Outer(linalg::from_dynamic(&m)))
+    }
+
+    // Trace norm of the partial transpose, Σ |λ_i|
+    fn partial_transpose_norm(&self, qubits: &[usize]) -> Result<f64, &'static str> {
+        let transposed = self.partial_transpose(qubits)?;
+        let (eigenvalues, _) = linalg::hermitian_eigen(linalg::to_dynamic(&transposed.0));
+        Ok(eigenvalues.iter().map(|v| v.abs()).sum())
+    }
+
+    /// Negativity `(‖ρ^T_B‖₁ - 1) / 2` with respect to the part B made of `qubits`
+    pub fn negativity(&self, qubits: &[usize]) -> Result<f64, &'static str> {
+        self.partial_transpose_norm(qubits).map(|norm| (norm - 1.0) / 2.0)
+    }
+
+    /// Logarithmic negativity `log₂ ‖ρ^T_B‖₁` with respect to the part B made of `qubits`
+    pub fn logarithmic_negativity(&self, qubits: &[usize]) -> Result<f64, &'static str> {
+        self.partial_transpose_norm(qubits).map(|norm| norm.log2())
+    }
+
+    /// Whether partial transpose with respect to the part B made of `qubits` is positive
+    /// semi-definite (PPT criterion).
+    ///
+    /// States failing the check are entangled; for 2×2 and 2×3 systems passing it implies
+    /// separability. In larger systems bound entangled states pass it as well.
+    pub fn is_ppt(&self, qubits: &[usize]) -> Result<bool, &'static str> {
+        let transposed = self.partial_transpose(qubits)?;
+        let (eigenvalues, _) = linalg::hermitian_eigen(linalg::to_dynamic(&transposed.0));
+        Ok(eigenvalues.iter().all(|&v| v > -TOLERANCE))
+    }
+}
+
+impl Ket<U4> {
+    /// Concurrence of the two qubit pure state, `|<ψ|σy⊗σy|ψ*>|`
+    pub fn concurrence(&self) -> f64 {
+        let normalized = self.0.normalize();
+        let flipped = Outer2::y2().tensor(Outer2::y2()).0 * normalized.conjugate();
+        normalized.dotc(&flipped).norm()
+    }
+}
+
+impl DensityMatrix<U4> {
+    /// Concurrence of the two qubit (possibly mixed) state, by Wootters formula
+    pub fn concurrence(&self) -> f64 {
+        let yy = Outer2::y2().tensor(Outer2::y2()).0;
+        let flipped = linalg::to_dynamic(&(yy * self.0.conjugate() * yy));
+
+        // eigenvalues of √ρ ρ̃ √ρ are squares of the ones in the formula
+        let sqrt = linalg::map_normal(linalg::to_dynamic(&self.0), |v| Complex::from(v.re.max(0.0).sqrt()));
+        let (eigenvalues, _) = linalg::hermitian_eigen(&sqrt * flipped * &sqrt);
+
+        let mut lambdas: Vec<f64> = eigenvalues.iter().map(|v| v.max(0.0).sqrt()).collect();
+        lambdas.sort_by(|a, b| b.partial_cmp(a).expect("eigenvalues are finite"));
+
+        (lambdas[0] - lambdas[1] - lambdas[2] - lambdas[3]).max(0.0)
+    }
+}
@@ -0,0 +1,93 @@
+use nalgebra::{DefaultAllocator, DMatrix, DVector, DimName, U1, VectorN};
+use nalgebra::allocator::Allocator;
+
+use {linalg, Ket, Outer, Complex};
+
+/// Order of the Trotter–Suzuki product formula.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trotter {
+    /// `Π e^{-iH_k dt}`, error O(dt²) per step
+    First,
+    /// Symmetric `Π e^{-iH_k dt/2} Π_reversed e^{-iH_k dt/2}`, error O(dt³) per step
+    Second,
+}
+
+// Eigendecomposition of the hamiltonian, failing for non-hermitian ones
+fn spectrum<D: DimName>(hamiltonian: &Outer<D>) -> Result<(Vec<f64>, DMatrix<Complex>), &'static str>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    let matrix = linalg::to_dynamic(&hamiltonian.0);
+    if !linalg::is_hermitian(&matrix) {
+        return Err("Hamiltonian should be hermitian");
+    }
+    Ok(linalg::hermitian_eigen(matrix))
+}
+
+// V e^{-iΛt} V†
+fn propagator(eigenvalues: &[f64], eigenvectors: &DMatrix<Complex>, t: f64) -> DMatrix<Complex> {
+    let phases = DVector::from_iterator(eigenvalues.len(), eigenvalues.iter().map(|&e| Complex::new(0.0, -e * t).exp()));
+    eigenvectors * DMatrix::from_diagonal(&phases) * eigenvectors.adjoint()
+}
+
+impl<D: DimName> Outer<D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    /// Time evolution operator `e^{-iHt}` of the hamiltonian (ħ = 1)
+    pub fn propagator(&self, t: f64) -> Result<Outer<D>, &'static str> {
+        let (eigenvalues, eigenvectors) = spectrum(self)?;
+        Ok(Outer(linalg::from_dynamic(&propagator(&eigenvalues, &eigenvectors, t))))
+    }
+}
+
+impl<D: DimName> Ket<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D>
+{
+    /// State after the evolution under the hamiltonian for time `t`, `e^{-iHt}|ψ>` (ħ = 1)
+    pub fn evolve(&self, hamiltonian: &Outer<D>, t: f64) -> Result<Ket<D>, &'static str> {
+        hamiltonian.propagator(t).map(|u| u * self)
+    }
+
+    /// States after the evolution under the hamiltonian for each of the `times`
+    ///
+    /// Hamiltonian is diagonalized once, so the times can be arbitrary (e.g. sampling Rabi
+    /// oscillations for a plot).
+    pub fn evolve_at(&self, hamiltonian: &Outer<D>, times: &[f64]) -> Result<Vec<Ket<D>>, &'static str> {
+        let (eigenvalues, eigenvectors) = spectrum(hamiltonian)?;
+        let dim = D::name().value();
+        let coordinates = eigenvectors.adjoint() * DVector::from_iterator(dim, self.0.iter().cloned());
+
+        Ok(times.iter().map(|&t| {
+            let rotated = DVector::from_fn(dim, |i, _| coordinates[i] * Complex::new(0.0, -eigenvalues[i] * t).exp());
+            Ket(VectorN::from_iterator_generic(D::name(), U1, (&eigenvectors * rotated).iter().cloned()))
+        }).collect())
+    }
+
+    /// State after the evolution under `H = Σ H_k` for time `t`, approximated with `steps`
+    /// Trotter–Suzuki steps of the given `order`
+    pub fn evolve_trotter(&self, terms: &[Outer<D>], t: f64, steps: usize, order: Trotter) -> Result<Ket<D>, &'static str> {
+        if steps == 0 {
+            return Err("Trotter evolution needs at least one step");
+        }
+        let dt = t / steps as f64;
+
+        let sequence = match order {
+            Trotter::First => terms.iter()
+                .map(|h| h.propagator(dt))
+                .collect::<Result<Vec<_>, _>>()?,
+            Trotter::Second => {
+                let halves = terms.iter()
+                    .map(|h| h.propagator(dt / 2.0))
+                    .collect::<Result<Vec<_>, _>>()?;
+                halves.iter().chain(halves.iter().rev()).cloned().collect()
+            },
+        };
+
+        let mut state = self.clone();
+        for _ in 0..steps {
+            for u in &sequence {
+                state = u * state;
+            }
+        }
+        Ok(state)
+    }
+}
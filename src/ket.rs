@@ -1,8 +1,7 @@
-use std::ops::{Mul, Add};
 use nalgebra::{DefaultAllocator, U1, VectorN, Vector2, DimName, U2, DimNameProd, DimNameMul, Vector};
 use nalgebra::allocator::Allocator;
 
-use {SQRT_2_INVERSE, Bra, Outer, Complex};
+use {dirac, linalg, SQRT_2_INVERSE, Bra, Outer, Complex};
 
 /// Generic ket. You can multiply it by bra to an outer product (or operator).
 #[derive(Clone, Debug, PartialEq)]
@@ -75,24 +74,51 @@ impl<D: DimName> Ket<D>
     /// Kronecker (tensor) product of two kets
     ///
     /// For qubits, it's possible to produce only 2 qubits state with this product.
+    /// Use `tensor` to combine kets of different dimensions.
     pub fn cross(self, other: Ket<D>) -> Ket<DimNameProd<D, D>>
         where
             D: DimNameMul<D>,
             DefaultAllocator: Allocator<Complex, DimNameProd<D, D>>
     {
-        let mut result = Vector::zeros_generic(DimNameProd::<D, D>::name(), U1);
-        let dim = D::name().value();
-        for i in 0..dim {
-            for j in 0..dim {
+        self.tensor(other)
+    }
+
+    /// Kronecker (tensor) product of kets of arbitrary dimensions
+    ///
+    /// `|a> ⊗ |b>`, where `self` occupies the most significant position.
+    pub fn tensor<B: DimName>(self, other: Ket<B>) -> Ket<DimNameProd<D, B>>
+        where
+            D: DimNameMul<B>,
+            DefaultAllocator: Allocator<Complex, B> + Allocator<Complex, DimNameProd<D, B>>
+    {
+        let mut result = Vector::zeros_generic(DimNameProd::<D, B>::name(), U1);
+        let dim_a = D::name().value();
+        let dim_b = B::name().value();
+        for i in 0..dim_a {
+            for j in 0..dim_b {
                 *result.get_mut(
-                    i*dim + j
-                ).expect("i, j in (dim, dim) range") = self.0[i] * other.0[j];
+                    i*dim_b + j
+                ).expect("i, j in (dim_a, dim_b) range") = self.0[i] * other.0[j];
             }
         }
         Ket(result)
     }
 
+    /// Apply a gate to the chosen qubits of the register in place.
+    ///
+    /// Qubit 0 is the most significant (leftmost) one. Gate acting on `k` qubits should be
+    /// of `2^k` dimension, with `qubits[0]` mapped to its most significant qubit. Only
+    /// amplitudes are updated, the full register operator is never built.
+    pub fn apply_gate<G: DimName>(&mut self, gate: &Outer<G>, qubits: &[usize]) -> Result<(), &'static str>
+        where DefaultAllocator: Allocator<Complex, G, G>
+    {
+        linalg::apply_gate(self.0.as_mut_slice(), gate.0.as_slice(), qubits)
+    }
+
     /// Cross product of basis (|up> & |down>) qubits picked from a bit string.
+    ///
+    /// Set bits pick up, which is labeled `0` in the Dirac output, by `sample` and by the
+    /// expression parser, so e.g. `from_bits(0b110)` of 3 qubits is `|001⟩` there.
     pub fn from_bits(number: u16) -> Result<Ket<D>, &'static str>
     {
         fn get_bit_at(input: usize, n: usize) -> bool {
@@ -134,7 +160,7 @@ impl<D: DimName> Ket<D>
             let mut ith = Complex::from(1.0);
             for k in 0..size_log2 {
                 let kth = if k < bit_space.len() { &bit_space[k] } else { def0 };
-                ith = ith *
+                ith *=
                     {
                         if get_bit_at(i, k) { kth.0.get(1).expect("always 2 elements") }
                         else { kth.0.get(0).expect("always 2 elements") }
@@ -147,38 +173,41 @@ impl<D: DimName> Ket<D>
     }
 }
 
-impl<D: DimName> Mul<Bra<D>> for Ket<D>
-    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
-{
-    type Output = Outer<D>;
-
-    fn mul(self, other: Bra<D>) -> Self::Output {
-        let mut m = other.0;
-        for f in m.iter_mut() { *f = f.conj(); }
-        Outer(self.0 * m)
-    }
-}
-
-impl<D: DimName> Add for Ket<D>
-    where DefaultAllocator: Allocator<Complex, D>
-{
-    type Output = Self;
-
-    fn add(self, other: Ket<D>) -> Self::Output {
-        Ket(self.0 + other.0)
-    }
-}
-
-impl<D: DimName> Mul<Complex> for Ket<D>
-    where DefaultAllocator: Allocator<Complex, D, U1>
-{
-    type Output = Self;
-
-    fn mul(self, other: Complex) -> Self::Output {
-        Ket(self.0 * other)
-    }
-}
-
+impl_binop!(
+    [D: DimName] Mul, mul for Ket<D>, Bra<D> => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>]
+    |ket, bra| Outer(&ket.0 * bra.0.conjugate())
+);
+
+impl_binop!(
+    [D: DimName] Add, add for Ket<D>, Ket<D> => Ket<D>
+    where [DefaultAllocator: Allocator<Complex, D>]
+    |a, b| Ket(&a.0 + &b.0)
+);
+
+impl_binop!(
+    [D: DimName] Sub, sub for Ket<D>, Ket<D> => Ket<D>
+    where [DefaultAllocator: Allocator<Complex, D>]
+    |a, b| Ket(&a.0 - &b.0)
+);
+
+impl_neg!(
+    [D: DimName] for Ket<D>
+    where [DefaultAllocator: Allocator<Complex, D>]
+    |a| Ket(-&a.0)
+);
+
+impl_scalar_op!(
+    [D: DimName] Mul, mul for Ket<D>, Complex => Ket<D>
+    where [DefaultAllocator: Allocator<Complex, D>]
+    |ket, scalar| Ket(&ket.0 * scalar)
+);
+
+impl_scalar_op!(
+    [D: DimName] Div, div for Ket<D>, Complex => Ket<D>
+    where [DefaultAllocator: Allocator<Complex, D>]
+    |ket, scalar| Ket(&ket.0 / scalar)
+);
 
 impl<D: DimName> From<Bra<D>> for Ket<D>
     where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
@@ -197,10 +226,9 @@ impl<D: DimName> From<VectorN<Complex, D>> for Ket<D>
 }
 
 impl<D: DimName> ::std::fmt::Display for Ket<D>
-    where DefaultAllocator: Allocator<Complex, D>,
-    DefaultAllocator: Allocator<usize, D>
+    where DefaultAllocator: Allocator<Complex, D>
 {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        write!(f, "{}", self.0)
+        f.write_str(&self.to_dirac(&dirac::formatter_options(f)))
     }
 }
@@ -0,0 +1,217 @@
+use nalgebra::{DefaultAllocator, DimName, U1};
+use nalgebra::allocator::Allocator;
+
+use dirac::Notation;
+use {Bra, Circuit, DBra, DKet, DOuter, DiracFormat, Ket, Outer, Complex};
+
+/// Layout of the LaTeX output of states and operators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatexStyle {
+    /// Sum of `\ket{}`/`\bra{}` terms (`braket` or `physics` package)
+    Dirac,
+    /// Components in `pmatrix` (`amsmath` package)
+    Matrix,
+}
+
+// `rows × columns` matrix from the element function
+fn pmatrix<F>(format: &DiracFormat, rows: usize, columns: usize, element: F) -> String
+    where F: Fn(usize, usize) -> Complex
+{
+    let body: Vec<String> = (0..rows).map(|i| {
+        (0..columns).map(|j| {
+            let c = element(i, j);
+            if format.is_zero(c.norm()) { "0".to_owned() } else { format.number(c, Notation::Latex) }
+        }).collect::<Vec<_>>().join(" & ")
+    }).collect();
+    format!("\\begin{{pmatrix}} {} \\end{{pmatrix}}", body.join(" \\\\ "))
+}
+
+fn ket(amplitudes: &[Complex], style: LatexStyle, format: &DiracFormat) -> String {
+    match style {
+        LatexStyle::Dirac => format.ket(amplitudes, Notation::Latex),
+        LatexStyle::Matrix => pmatrix(format, amplitudes.len(), 1, |i, _| amplitudes[i]),
+    }
+}
+
+// Bra stores components of its dual ket, so the row is conjugated
+fn bra(amplitudes: &[Complex], style: LatexStyle, format: &DiracFormat) -> String {
+    match style {
+        LatexStyle::Dirac => format.bra(amplitudes, Notation::Latex),
+        LatexStyle::Matrix => pmatrix(format, 1, amplitudes.len(), |_, j| amplitudes[j].conj()),
+    }
+}
+
+// Column-major square matrix
+fn operator(matrix: &[Complex], dim: usize, style: LatexStyle, format: &DiracFormat) -> String {
+    match style {
+        LatexStyle::Dirac => format.operator(matrix, dim, Notation::Latex),
+        LatexStyle::Matrix => pmatrix(format, dim, dim, |i, j| matrix[j * dim + i]),
+    }
+}
+
+impl<D: DimName> Ket<D>
+    where DefaultAllocator: Allocator<Complex, D>
+{
+    /// LaTeX code of the ket, amplitudes are printed with the options of `format`
+    pub fn to_latex(&self, style: LatexStyle, format: &DiracFormat) -> String {
+        ket(self.0.as_slice(), style, format)
+    }
+}
+
+impl<D: DimName> Bra<D>
+    where DefaultAllocator: Allocator<Complex, U1, D>
+{
+    /// LaTeX code of the bra, amplitudes are printed with the options of `format`
+    pub fn to_latex(&self, style: LatexStyle, format: &DiracFormat) -> String {
+        bra(self.0.as_slice(), style, format)
+    }
+}
+
+impl<D: DimName> Outer<D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    /// LaTeX code of the operator, elements are printed with the options of `format`
+    pub fn to_latex(&self, style: LatexStyle, format: &DiracFormat) -> String {
+        operator(self.0.as_slice(), D::name().value(), style, format)
+    }
+}
+
+impl DKet {
+    /// LaTeX code of the ket, amplitudes are printed with the options of `format`
+    pub fn to_latex(&self, style: LatexStyle, format: &DiracFormat) -> String {
+        ket(self.0.as_slice(), style, format)
+    }
+}
+
+impl DBra {
+    /// LaTeX code of the bra, amplitudes are printed with the options of `format`
+    pub fn to_latex(&self, style: LatexStyle, format: &DiracFormat) -> String {
+        bra(self.0.as_slice(), style, format)
+    }
+}
+
+impl DOuter {
+    /// LaTeX code of the operator, elements are printed with the options of `format`
+    pub fn to_latex(&self, style: LatexStyle, format: &DiracFormat) -> String {
+        operator(self.0.as_slice(), self.dim(), style, format)
+    }
+}
+
+// Gate name with the characters special in LaTeX math mode escaped
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' => escaped.push_str("\\backslash{}"),
+            '~' => escaped.push_str("\\sim{}"),
+            '^' => escaped.push_str("\\hat{}"),
+            '_' | '&' | '%' | '#' | '$' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// LaTeX label of the gate name, e.g. `S^\dagger` for "S†" or `R_x` for "Rx"
+fn gate_label(name: &str) -> String {
+    let (base, dagger) = match name.strip_suffix('†') {
+        Some(base) => (base, "^\\dagger"),
+        None => (name, ""),
+    };
+    let base = match base {
+        "Rx" | "Ry" | "Rz" => format!("R_{}", &base[1..]),
+        "U3" => "U_3".to_owned(),
+        _ => match base.strip_prefix('√') {
+            Some(radicand) => format!("\\sqrt{{{}}}", escape(radicand)),
+            None => escape(base),
+        },
+    };
+    format!("{}{}", base, dagger)
+}
+
+impl Circuit {
+    /// Circuit diagram for the `quantikz` LaTeX package
+    ///
+    /// Gates acting on disjoint ranges of wires share a column. Controlled gates and swaps
+    /// built with the named methods are drawn with controls and targets, other multi-qubit
+    /// gates (including the ones pushed with `gate`, whatever their name) as boxes.
+    pub fn to_quantikz(&self) -> String {
+        let n = self.n_qubits();
+        let mut columns: Vec<Vec<String>> = Vec::new();
+        let mut depth = vec![0; n];
+
+        for instruction in self.instructions() {
+            let qubits = instruction.qubits();
+            let top = *qubits.iter().min().expect("gates act on at least one qubit");
+            let bottom = *qubits.iter().max().expect("gates act on at least one qubit");
+
+            // vertical wires block the whole range of the gate
+            let column = (top..=bottom).map(|q| depth[q]).max().unwrap_or(0);
+            for d in &mut depth[top..=bottom] {
+                *d = column + 1;
+            }
+            if columns.len() <= column {
+                columns.push(vec!["\\qw".to_owned(); n]);
+            }
+            let cells = &mut columns[column];
+
+            let offset = |from: usize, to: usize| to as isize - from as isize;
+            // names of arbitrary gates say nothing about their operators
+            let name = Some(instruction.name().trim_end_matches('†')).filter(|_| instruction.is_builtin());
+            let controlled = |cells: &mut Vec<String>, controls: &[usize], target: usize| {
+                for &c in controls {
+                    cells[c] = format!("\\ctrl{{{}}}", offset(c, target));
+                }
+            };
+            match (name, qubits) {
+                (Some("CNOT"), &[c, t]) => {
+                    controlled(cells, &[c], t);
+                    cells[t] = "\\targ{}".to_owned();
+                },
+                (Some("CCNOT"), &[c1, c2, t]) => {
+                    controlled(cells, &[c1, c2], t);
+                    cells[t] = "\\targ{}".to_owned();
+                },
+                (Some("CZ"), &[c, t]) => {
+                    controlled(cells, &[c], t);
+                    cells[t] = "\\control{}".to_owned();
+                },
+                (Some("CY"), &[c, t]) | (Some("CP"), &[c, t]) => {
+                    controlled(cells, &[c], t);
+                    cells[t] = format!("\\gate{{{}}}", gate_label(&instruction.name()[1..]));
+                },
+                (Some("SWAP"), &[a, b]) => {
+                    cells[a] = format!("\\swap{{{}}}", offset(a, b));
+                    cells[b] = "\\targX{}".to_owned();
+                },
+                (Some("CSWAP"), &[c, a, b]) => {
+                    controlled(cells, &[c], a);
+                    cells[a] = format!("\\swap{{{}}}", offset(a, b));
+                    cells[b] = "\\targX{}".to_owned();
+                },
+                (_, &[q]) => cells[q] = format!("\\gate{{{}}}", gate_label(instruction.name())),
+                _ => {
+                    let label = gate_label(instruction.name());
+                    if qubits.windows(2).all(|pair| pair[1] == pair[0] + 1) {
+                        cells[top] = format!("\\gate[{}]{{{}}}", qubits.len(), label);
+                    } else {
+                        for (k, &q) in qubits.iter().enumerate() {
+                            cells[q] = format!("\\gate{{{}_{{{}}}}}", label, k);
+                        }
+                    }
+                },
+            }
+        }
+
+        let rows: Vec<String> = (0..n).map(|q| {
+            let mut row = vec![format!("\\lstick{{$q_{{{}}}$}}", q)];
+            row.extend(columns.iter().map(|column| column[q].clone()));
+            row.push("\\qw".to_owned());
+            row.join(" & ")
+        }).collect();
+        format!("\\begin{{quantikz}}\n{}\n\\end{{quantikz}}", rows.join(" \\\\\n"))
+    }
+}
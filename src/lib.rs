@@ -5,21 +5,52 @@
 extern crate num_complex;
 extern crate nalgebra;
 extern crate typenum;
+extern crate rand;
 
 #[cfg(test)] extern crate assert_approx_eq;
 
 
 pub use num_complex::Complex64 as Complex;
 pub(crate) const SQRT_2_INVERSE: f64 = std::f64::consts::FRAC_1_SQRT_2;
+pub(crate) const TOLERANCE: f64 = 1e-9;
 
+#[macro_use] mod macros;
+#[macro_use] mod literals;
+
+mod basis;
+mod bloch;
 mod bra;
+mod channel;
+mod circuit;
+mod density;
+mod dirac;
+mod dynamic;
+mod entanglement;
+mod evolution;
 mod ket;
+mod latex;
+mod linalg;
+mod lindblad;
+mod measure;
 mod outer;
+pub mod parse;
+mod trajectory;
 #[cfg(test)] mod tests;
 
+pub use basis::Basis;
 pub use bra::Bra;
+pub use channel::Channel;
+pub use circuit::{Circuit, Instruction};
+pub use density::DensityMatrix;
+pub use dirac::{DiracFormat, Labels};
+pub use dynamic::{DKet, DBra, DOuter, TryAdd, TryMul};
+pub use entanglement::SchmidtDecomposition;
+pub use evolution::Trotter;
 pub use ket::Ket;
+pub use latex::LatexStyle;
+pub use lindblad::Lindblad;
 pub use outer::Outer;
+pub use trajectory::Trajectories;
 
 /// 2-dimension bra
 pub type Bra2 = Bra<nalgebra::U2>;
@@ -0,0 +1,183 @@
+use nalgebra::{DefaultAllocator, DMatrix, DimName, MatrixMN, Schur, SymmetricEigen, SVD};
+use nalgebra::allocator::Allocator;
+
+use {Complex, TOLERANCE};
+
+/// Copy a statically sized square matrix into a dynamically sized one.
+pub(crate) fn to_dynamic<D: DimName>(m: &MatrixMN<Complex, D, D>) -> DMatrix<Complex>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    let dim = D::name().value();
+    DMatrix::from_iterator(dim, dim, m.iter().cloned())
+}
+
+/// Copy a dynamically sized square matrix back into a statically sized one.
+///
+/// Caller guarantees that dimensions match.
+pub(crate) fn from_dynamic<D: DimName>(m: &DMatrix<Complex>) -> MatrixMN<Complex, D, D>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    MatrixMN::from_iterator_generic(D::name(), D::name(), m.iter().cloned())
+}
+
+/// Apply scalar function to a normal matrix through its eigendecomposition.
+///
+/// For a normal matrix the (complex) Schur form is diagonal, so `A = Q T Q†` and
+/// `f(A) = Q f(T) Q†`. For non-normal matrices the result is not meaningful.
+pub(crate) fn map_normal<F>(m: DMatrix<Complex>, f: F) -> DMatrix<Complex>
+    where F: Fn(Complex) -> Complex
+{
+    let dim = m.nrows();
+    let (q, t) = Schur::new(m).unpack();
+    let diagonal = DMatrix::from_fn(dim, dim, |i, j| if i == j { f(t[(i, i)]) } else { Complex::from(0.0) });
+    &q * diagonal * q.adjoint()
+}
+
+/// Number of qubits in the register of given dimension.
+pub(crate) fn qubit_count(dim: usize) -> Result<usize, &'static str> {
+    if dim == 0 || dim & (dim - 1) != 0 {
+        return Err("Should be a power of 2 to represent a qubit register");
+    }
+    Ok(dim.trailing_zeros() as usize)
+}
+
+/// Apply `gate` (column-major, 2^k × 2^k) to the `qubits` of the register `state` in place.
+///
+/// Qubit 0 is the most significant one (the leftmost factor of the tensor product), and
+/// `qubits[0]` is mapped to the most significant qubit of the gate.
+pub(crate) fn apply_gate(state: &mut [Complex], gate: &[Complex], qubits: &[usize]) -> Result<(), &'static str> {
+    let n = qubit_count(state.len())?;
+    if qubits.len() > n {
+        return Err("Gate acts on more qubits than the register has");
+    }
+    let gate_dim = 1 << qubits.len();
+
+    if gate.len() != gate_dim * gate_dim {
+        return Err("Gate dimension does not match the number of qubits it is applied to");
+    }
+    for (i, &q) in qubits.iter().enumerate() {
+        if q >= n {
+            return Err("Qubit index is out of the register range");
+        }
+        if qubits[..i].contains(&q) {
+            return Err("Gate cannot be applied to the same qubit twice");
+        }
+    }
+
+    let masks: Vec<usize> = qubits.iter().map(|q| 1 << (n - 1 - q)).collect();
+    let target_mask = masks.iter().fold(0, |acc, m| acc | m);
+
+    let mut indices = vec![0; gate_dim];
+    let mut amplitudes = vec![Complex::from(0.0); gate_dim];
+
+    for base in (0..state.len()).filter(|base| base & target_mask == 0) {
+        for (m, index) in indices.iter_mut().enumerate() {
+            *index = masks.iter().enumerate()
+                .filter(|&(k, _)| m & (1 << (qubits.len() - 1 - k)) != 0)
+                .fold(base, |acc, (_, mask)| acc | mask);
+        }
+        for (m, amplitude) in amplitudes.iter_mut().enumerate() {
+            *amplitude = indices.iter().enumerate()
+                .fold(Complex::from(0.0), |acc, (j, &index)| acc + gate[j * gate_dim + m] * state[index]);
+        }
+        for (index, amplitude) in indices.iter().zip(amplitudes.iter()) {
+            state[*index] = *amplitude;
+        }
+    }
+
+    Ok(())
+}
+
+/// Eigendecomposition of a hermitian matrix: real eigenvalues and eigenvectors as columns.
+pub(crate) fn hermitian_eigen(m: DMatrix<Complex>) -> (Vec<f64>, DMatrix<Complex>) {
+    let eigen = SymmetricEigen::new(m);
+    (eigen.eigenvalues.iter().cloned().collect(), eigen.eigenvectors)
+}
+
+/// Singular value decomposition `M = U Σ V†`: `U`, singular values and `V†`.
+pub(crate) fn svd(m: DMatrix<Complex>) -> (DMatrix<Complex>, Vec<f64>, DMatrix<Complex>) {
+    let svd = SVD::new(m, true, true);
+    (
+        svd.u.expect("u is computed"),
+        svd.singular_values.iter().cloned().collect(),
+        svd.v_t.expect("v_t is computed"),
+    )
+}
+
+/// Whether the matrix is hermitian up to the tolerance.
+pub(crate) fn is_hermitian(m: &DMatrix<Complex>) -> bool {
+    (m - m.adjoint()).iter().all(|v| v.norm() < TOLERANCE)
+}
+
+/// Trace out all subsystems except `keep` (in the order given) of the operator acting on
+/// the tensor product of subsystems of `dims` dimensions (subsystem 0 is the most significant).
+pub(crate) fn partial_trace(m: &DMatrix<Complex>, dims: &[usize], keep: &[usize]) -> Result<DMatrix<Complex>, &'static str> {
+    if dims.iter().product::<usize>() != m.nrows() {
+        return Err("Subsystem dimensions do not multiply to the operator dimension");
+    }
+    for (i, &k) in keep.iter().enumerate() {
+        if k >= dims.len() {
+            return Err("Subsystem index is out of range");
+        }
+        if keep[..i].contains(&k) {
+            return Err("Subsystem cannot be kept twice");
+        }
+    }
+
+    let traced: Vec<usize> = (0..dims.len()).filter(|s| !keep.contains(s)).collect();
+    let kept_dim: usize = keep.iter().map(|&s| dims[s]).product();
+    let traced_dim: usize = traced.iter().map(|&s| dims[s]).product();
+
+    // weight of every subsystem digit in the full index
+    let mut strides = vec![1; dims.len()];
+    for s in (0..dims.len().saturating_sub(1)).rev() {
+        strides[s] = strides[s + 1] * dims[s + 1];
+    }
+    let offset = |subsystems: &[usize], mut index: usize| -> usize {
+        let mut result = 0;
+        for &s in subsystems.iter().rev() {
+            result += (index % dims[s]) * strides[s];
+            index /= dims[s];
+        }
+        result
+    };
+
+    let kept_offsets: Vec<usize> = (0..kept_dim).map(|i| offset(keep, i)).collect();
+    let traced_offsets: Vec<usize> = (0..traced_dim).map(|i| offset(&traced, i)).collect();
+
+    Ok(DMatrix::from_fn(kept_dim, kept_dim, |r, c| {
+        traced_offsets.iter()
+            .fold(Complex::from(0.0), |acc, t| acc + m[(kept_offsets[r] + t, kept_offsets[c] + t)])
+    }))
+}
+
+/// Partial transpose of the `transpose` subsystems of the operator acting on the tensor product
+/// of subsystems of `dims` dimensions (subsystem 0 is the most significant).
+pub(crate) fn partial_transpose(m: &DMatrix<Complex>, dims: &[usize], transpose: &[usize]) -> Result<DMatrix<Complex>, &'static str> {
+    if dims.iter().product::<usize>() != m.nrows() {
+        return Err("Subsystem dimensions do not multiply to the operator dimension");
+    }
+    if transpose.iter().any(|&s| s >= dims.len()) {
+        return Err("Subsystem index is out of range");
+    }
+
+    let digits = |mut index: usize| -> Vec<usize> {
+        let mut result = vec![0; dims.len()];
+        for s in (0..dims.len()).rev() {
+            result[s] = index % dims[s];
+            index /= dims[s];
+        }
+        result
+    };
+    let compose = |digits: &[usize]| -> usize {
+        digits.iter().zip(dims.iter()).fold(0, |acc, (d, dim)| acc * dim + d)
+    };
+
+    Ok(DMatrix::from_fn(m.nrows(), m.ncols(), |r, c| {
+        let (mut row, mut column) = (digits(r), digits(c));
+        for &s in transpose {
+            ::std::mem::swap(&mut row[s], &mut column[s]);
+        }
+        m[(compose(&row), compose(&column))]
+    }))
+}
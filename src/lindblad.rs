@@ -0,0 +1,173 @@
+use nalgebra::{DefaultAllocator, DMatrix, DVector, DimName, U1};
+use nalgebra::allocator::Allocator;
+
+use {linalg, DensityMatrix, Outer, Complex};
+
+/// Lindblad master equation of an open system (ħ = 1),
+/// `dρ/dt = -i[H, ρ] + Σ (L ρ L† - ½{L†L, ρ})`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lindblad<D: DimName>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    hamiltonian: Outer<D>,
+    jumps: Vec<Outer<D>>,
+}
+
+// Right-hand side of the equation on runtime-sized matrices
+struct Generator {
+    hamiltonian: DMatrix<Complex>,
+    jumps: Vec<DMatrix<Complex>>,
+    decay: DMatrix<Complex>,
+}
+
+impl Generator {
+    fn derivative(&self, rho: &DMatrix<Complex>) -> DMatrix<Complex> {
+        let commutator = &self.hamiltonian * rho - rho * &self.hamiltonian;
+        let anticommutator = &self.decay * rho + rho * &self.decay;
+        let jumps = self.jumps.iter()
+            .fold(DMatrix::zeros(rho.nrows(), rho.ncols()), |acc, l| acc + l * rho * l.adjoint());
+        commutator * Complex::new(0.0, -1.0) + jumps - anticommutator * Complex::from(0.5)
+    }
+
+    fn rk4_step(&self, rho: &DMatrix<Complex>, dt: f64) -> DMatrix<Complex> {
+        let half = Complex::from(dt / 2.0);
+        let full = Complex::from(dt);
+        let k1 = self.derivative(rho);
+        let k2 = self.derivative(&(rho + &k1 * half));
+        let k3 = self.derivative(&(rho + &k2 * half));
+        let k4 = self.derivative(&(rho + &k3 * full));
+        rho + (k1 + k2 * Complex::from(2.0) + k3 * Complex::from(2.0) + k4) * Complex::from(dt / 6.0)
+    }
+}
+
+impl<D: DimName> Lindblad<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+{
+    /// Master equation with the hamiltonian (should be hermitian) and jump operators `L`
+    /// (including the rates, e.g. `√γ σ-` for the decay with the rate γ)
+    pub fn new(hamiltonian: Outer<D>, jumps: Vec<Outer<D>>) -> Result<Lindblad<D>, &'static str> {
+        if !linalg::is_hermitian(&linalg::to_dynamic(&hamiltonian.0)) {
+            return Err("Hamiltonian should be hermitian");
+        }
+        Ok(Lindblad { hamiltonian, jumps })
+    }
+
+    /// Hamiltonian of the system
+    pub fn hamiltonian(&self) -> &Outer<D> {
+        &self.hamiltonian
+    }
+
+    /// Jump operators of the system
+    pub fn jumps(&self) -> &[Outer<D>] {
+        &self.jumps
+    }
+
+    fn generator(&self) -> Generator {
+        let jumps: Vec<DMatrix<Complex>> = self.jumps.iter().map(|l| linalg::to_dynamic(&l.0)).collect();
+        let dim = D::name().value();
+        let decay = jumps.iter().fold(DMatrix::zeros(dim, dim), |acc, l| acc + l.adjoint() * l);
+        Generator { hamiltonian: linalg::to_dynamic(&self.hamiltonian.0), jumps, decay }
+    }
+
+    /// Time derivative of the state, `dρ/dt`
+    pub fn derivative(&self, state: &DensityMatrix<D>) -> Outer<D> {
+        Outer(linalg::from_dynamic(&self.generator().derivative(&linalg::to_dynamic(&state.0))))
+    }
+
+    /// State after time `t`, integrated with `steps` fixed RK4 steps
+    pub fn evolve(&self, state: &DensityMatrix<D>, t: f64, steps: usize) -> Result<DensityMatrix<D>, &'static str> {
+        self.evolve_with(state, t, steps, |_, _| ())
+    }
+
+    /// State after time `t`, integrated with `steps` fixed RK4 steps.
+    ///
+    /// `callback` is called with the time and the state at the start and after every step,
+    /// e.g. to record expectation values.
+    pub fn evolve_with<F>(&self, state: &DensityMatrix<D>, t: f64, steps: usize, mut callback: F) -> Result<DensityMatrix<D>, &'static str>
+        where F: FnMut(f64, &DensityMatrix<D>)
+    {
+        if steps == 0 {
+            return Err("Integration needs at least one step");
+        }
+        let generator = self.generator();
+        let dt = t / steps as f64;
+
+        let mut rho = linalg::to_dynamic(&state.0);
+        callback(0.0, state);
+        for step in 1..=steps {
+            rho = generator.rk4_step(&rho, dt);
+            callback(step as f64 * dt, &DensityMatrix(linalg::from_dynamic(&rho)));
+        }
+        Ok(DensityMatrix(linalg::from_dynamic(&rho)))
+    }
+
+    /// State after time `t`, integrated with RK4 steps adapted to keep the local error
+    /// (estimated by step doubling) under `tolerance`
+    pub fn evolve_adaptive(&self, state: &DensityMatrix<D>, t: f64, tolerance: f64) -> Result<DensityMatrix<D>, &'static str> {
+        self.evolve_adaptive_with(state, t, tolerance, |_, _| ())
+    }
+
+    /// Adaptive version of `evolve_with`, `callback` is called at the start and after every
+    /// accepted step
+    ///
+    /// Fails if the step has to be shrunk below `t * 1e-12` to meet the tolerance, e.g. when
+    /// the tolerance is under the rounding error of the state.
+    pub fn evolve_adaptive_with<F>(&self, state: &DensityMatrix<D>, t: f64, tolerance: f64, mut callback: F) -> Result<DensityMatrix<D>, &'static str>
+        where F: FnMut(f64, &DensityMatrix<D>)
+    {
+        if tolerance <= 0.0 {
+            return Err("Tolerance should be positive");
+        }
+        let generator = self.generator();
+
+        let mut rho = linalg::to_dynamic(&state.0);
+        let mut time = 0.0;
+        let mut dt = t / 100.0;
+        let min_dt = t * 1e-12;
+        callback(0.0, state);
+
+        while time < t {
+            dt = dt.min(t - time);
+            let full = generator.rk4_step(&rho, dt);
+            let halves = generator.rk4_step(&generator.rk4_step(&rho, dt / 2.0), dt / 2.0);
+            let error = (&full - &halves).norm();
+
+            if error <= tolerance {
+                // Richardson extrapolation of the two estimates
+                rho = &halves + (&halves - full) / Complex::from(15.0);
+                time += dt;
+                callback(time, &DensityMatrix(linalg::from_dynamic(&rho)));
+            } else if dt <= min_dt || error.is_nan() {
+                return Err("Step size underflow");
+            }
+            let factor = if error > 0.0 { 0.9 * (tolerance / error).powf(0.2) } else { 2.0 };
+            dt *= factor.clamp(0.2, 2.0);
+        }
+        Ok(DensityMatrix(linalg::from_dynamic(&rho)))
+    }
+
+    /// Steady state, `dρ/dt = 0`
+    ///
+    /// Fails if the steady state is not unique.
+    pub fn steady_state(&self) -> Result<DensityMatrix<D>, &'static str> {
+        let generator = self.generator();
+        let dim = D::name().value();
+
+        // columns of the superoperator are images of |a><b| in the column-major vectorization
+        let mut superoperator = DMatrix::zeros(dim * dim, dim * dim);
+        for column in 0..dim * dim {
+            let basis = DMatrix::from_fn(dim, dim, |r, c| if r + c * dim == column { 1.0.into() } else { 0.0.into() });
+            superoperator.column_mut(column).copy_from_slice(generator.derivative(&basis).as_slice());
+        }
+        // the equations are linearly dependent (trace is preserved), replace one with Tr(ρ) = 1
+        for column in 0..dim * dim {
+            superoperator[(0, column)] = if column % (dim + 1) == 0 { 1.0.into() } else { 0.0.into() };
+        }
+        let rhs = DVector::from_fn(dim * dim, |i, _| if i == 0 { 1.0.into() } else { 0.0.into() });
+
+        let solution = superoperator.lu().solve(&rhs).ok_or("Steady state is not unique")?;
+        let rho = DMatrix::from_column_slice(dim, dim, solution.as_slice());
+        let hermitian = (&rho + rho.adjoint()) / Complex::from(2.0);
+        DensityMatrix::new(Outer(linalg::from_dynamic(&hermitian)))
+    }
+}
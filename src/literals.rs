@@ -0,0 +1,247 @@
+// Dirac notation literals: `ket!(|01>)`, `bra!(<+|)`, `op!(|0><1|)`.
+//
+// Unicode angle brackets are not valid Rust tokens, so the macros take ASCII `|a>` and `<a|`.
+// Labels are split into qubit symbols at compile time and folded into the tensor product of
+// qubit kets, which makes the dimension a part of the type.
+
+/// Ket in Dirac notation, e.g. `ket!(|01>)` or `ket!(0.6|0> - 0.8|1>)`.
+///
+/// Labels are made of qubit symbols `0`, `1`, `+` and `-`, and a label of `n` symbols gives
+/// `Ket<U(2^n)>`. Terms are added or subtracted, with optional coefficients which are single
+/// tokens of `f64` or `Complex` type: literals, names or parenthesized expressions. The ket is
+/// not normalized.
+///
+/// Malformed labels and terms are compile errors. A run of binary digits is a single Rust token
+/// which is split by the table of all runs of up to 6 digits, so longer runs should be broken
+/// up with spaces:
+///
+/// ```
+/// #[macro_use] extern crate brakets;
+///
+/// # fn main() {
+/// let ket = ket!(|000 0001>);
+/// assert_eq!(brakets::DKet::from(ket), brakets::DKet::basis(128, 1).unwrap());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! ket {
+    ($($tokens:tt)+) => { $crate::__dirac!(@first ket $($tokens)+) };
+}
+
+/// Bra in Dirac notation, e.g. `bra!(<+|)` or `bra!(0.6<0| + 0.8<1|)`.
+///
+/// Labels and coefficients are the same as in `ket!`, `c<a|` being `c` times `<a|`, i.e. the dual
+/// of `conj(c)|a>`.
+#[macro_export]
+macro_rules! bra {
+    ($($tokens:tt)+) => { $crate::__dirac!(@first bra $($tokens)+) };
+}
+
+/// Operator in Dirac notation, e.g. `op!(|0><1|)` or `op!(|00><00| - |11><11|)`.
+///
+/// Labels and coefficients are the same as in `ket!`; labels of the ket and the bra of a term
+/// should be of the same length.
+#[macro_export]
+macro_rules! op {
+    ($($tokens:tt)+) => { $crate::__dirac!(@first op $($tokens)+) };
+}
+
+// Token muncher behind `ket!`, `bra!` and `op!`.
+//
+// Term states carry the kind of the literal, the expression built so far and the coefficient of
+// the current term. Label states start with the collected qubit symbols, so that `@split` can
+// append the digits of a literal to any of them.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dirac {
+    // sign of the first term
+    (@first $kind:ident - $($rest:tt)+) => { $crate::__dirac!(@term $kind [] (-1.0) $($rest)+) };
+    (@first $kind:ident $($rest:tt)+) => { $crate::__dirac!(@term $kind [] (1.0) $($rest)+) };
+
+    // operators between the terms
+    (@next $kind:ident [$($sum:tt)+]) => { $($sum)+ };
+    (@next $kind:ident [$($sum:tt)+] + $($rest:tt)+) => { $crate::__dirac!(@term $kind [$($sum)+ +] (1.0) $($rest)+) };
+    (@next $kind:ident [$($sum:tt)+] - $($rest:tt)+) => { $crate::__dirac!(@term $kind [$($sum)+ -] (1.0) $($rest)+) };
+    (@next $kind:ident [$($sum:tt)+] $($rest:tt)+) => {
+        compile_error!(concat!("expected `+` or `-` between the terms, found `", stringify!($($rest)+), "`"))
+    };
+
+    // opening of the term, after an optional coefficient
+    (@term ket $sum:tt $sign:tt | $($rest:tt)+) => { $crate::__dirac!(@ket [] ket $sum ($sign) $($rest)+) };
+    (@term ket $sum:tt $sign:tt $c:tt | $($rest:tt)+) => { $crate::__dirac!(@ket [] ket $sum ($sign * $c) $($rest)+) };
+    (@term op $sum:tt $sign:tt | $($rest:tt)+) => { $crate::__dirac!(@ket [] op $sum ($sign) $($rest)+) };
+    (@term op $sum:tt $sign:tt $c:tt | $($rest:tt)+) => { $crate::__dirac!(@ket [] op $sum ($sign * $c) $($rest)+) };
+    (@term bra $sum:tt $sign:tt < $($rest:tt)+) => { $crate::__dirac!(@bra [] [] $sum ($sign) $($rest)+) };
+    (@term bra $sum:tt $sign:tt <- $($rest:tt)+) => { $crate::__dirac!(@bra [left] [] $sum ($sign) $($rest)+) };
+    (@term bra $sum:tt $sign:tt $c:tt < $($rest:tt)+) => { $crate::__dirac!(@bra [] [] $sum ($sign * $c) $($rest)+) };
+    (@term bra $sum:tt $sign:tt $c:tt <- $($rest:tt)+) => { $crate::__dirac!(@bra [left] [] $sum ($sign * $c) $($rest)+) };
+    (@term $kind:ident $sum:tt $sign:tt $($rest:tt)*) => {
+        compile_error!(concat!("expected a ", stringify!($kind), " term in Dirac notation, found `", stringify!($($rest)*), "`"))
+    };
+
+    // ket label, closed by `>` (or by `->` ending with the minus)
+    (@ket [$($s:ident)*] $kind:ident $sum:tt $c:tt > $($rest:tt)*) => { $crate::__dirac!(@ket_end [$($s)*] $kind $sum $c $($rest)*) };
+    (@ket [$($s:ident)*] $kind:ident $sum:tt $c:tt -> $($rest:tt)*) => { $crate::__dirac!(@ket_end [$($s)* left] $kind $sum $c $($rest)*) };
+    (@ket [$($s:ident)*] $kind:ident $sum:tt $c:tt + $($rest:tt)*) => { $crate::__dirac!(@ket [$($s)* right] $kind $sum $c $($rest)*) };
+    (@ket [$($s:ident)*] $kind:ident $sum:tt $c:tt - $($rest:tt)*) => { $crate::__dirac!(@ket [$($s)* left] $kind $sum $c $($rest)*) };
+    (@ket [$($s:ident)*] $kind:ident $sum:tt $c:tt $bits:tt $($rest:tt)*) => { $crate::__dirac!(@split $bits @ket [$($s)*] $kind $sum $c $($rest)*) };
+    (@ket [$($s:ident)*] $kind:ident $sum:tt $c:tt) => { compile_error!("unterminated ket, expected `>`") };
+
+    (@ket_end [$($s:ident)*] ket [$($sum:tt)*] $c:tt $($rest:tt)*) => {
+        $crate::__dirac!(@next ket [$($sum)* ($crate::__dirac!(@product $($s)*) * $crate::Complex::from $c)] $($rest)*)
+    };
+    (@ket_end [$($s:ident)*] op $sum:tt $c:tt < $($rest:tt)*) => { $crate::__dirac!(@bra [] [$($s)*] $sum $c $($rest)*) };
+    (@ket_end [$($s:ident)*] op $sum:tt $c:tt <- $($rest:tt)*) => { $crate::__dirac!(@bra [left] [$($s)*] $sum $c $($rest)*) };
+    (@ket_end [$($s:ident)*] op $sum:tt $c:tt $($rest:tt)*) => { compile_error!("expected a bra after the ket of the operator term") };
+
+    // bra label, closed by `|`; the second list holds the ket of the operator term, empty for `bra!`
+    (@bra [$($s:ident)*] $ket:tt $sum:tt $c:tt | $($rest:tt)*) => { $crate::__dirac!(@bra_end [$($s)*] $ket $sum $c $($rest)*) };
+    (@bra [$($s:ident)*] $ket:tt $sum:tt $c:tt + $($rest:tt)*) => { $crate::__dirac!(@bra [$($s)* right] $ket $sum $c $($rest)*) };
+    (@bra [$($s:ident)*] $ket:tt $sum:tt $c:tt - $($rest:tt)*) => { $crate::__dirac!(@bra [$($s)* left] $ket $sum $c $($rest)*) };
+    (@bra [$($s:ident)*] $ket:tt $sum:tt $c:tt $bits:tt $($rest:tt)*) => { $crate::__dirac!(@split $bits @bra [$($s)*] $ket $sum $c $($rest)*) };
+    (@bra [$($s:ident)*] $ket:tt $sum:tt $c:tt) => { compile_error!("unterminated bra, expected `|`") };
+
+    (@bra_end [$($s:ident)*] [] [$($sum:tt)*] $c:tt $($rest:tt)*) => {
+        $crate::__dirac!(@next bra [$($sum)* ($crate::Bra::from($crate::__dirac!(@product $($s)*)) * $crate::Complex::from $c)] $($rest)*)
+    };
+    (@bra_end [$($s:ident)*] [$($k:ident)+] [$($sum:tt)*] $c:tt $($rest:tt)*) => {
+        $crate::__dirac!(@next op [$($sum)* (
+            $crate::__dirac!(@product $($k)+) * $crate::Bra::from($crate::__dirac!(@product $($s)*)) * $crate::Complex::from $c
+        )] $($rest)*)
+    };
+
+    // tensor product of the qubit kets of the label
+    (@product $first:ident $($s:ident)*) => { $crate::Ket2::$first()$(.tensor($crate::Ket2::$s()))* };
+    (@product) => { compile_error!("empty label") };
+
+    // binary digits of the label, appended to the symbols of the label state
+    (@split 0 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up] $($x)*) };
+    (@split 1 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down] $($x)*) };
+    (@split 00 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up] $($x)*) };
+    (@split 01 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down] $($x)*) };
+    (@split 10 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up] $($x)*) };
+    (@split 11 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down] $($x)*) };
+    (@split 000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up] $($x)*) };
+    (@split 001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down] $($x)*) };
+    (@split 010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up] $($x)*) };
+    (@split 011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down] $($x)*) };
+    (@split 100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up] $($x)*) };
+    (@split 101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down] $($x)*) };
+    (@split 110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up] $($x)*) };
+    (@split 111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down] $($x)*) };
+    (@split 0000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up up] $($x)*) };
+    (@split 0001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up down] $($x)*) };
+    (@split 0010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down up] $($x)*) };
+    (@split 0011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down down] $($x)*) };
+    (@split 0100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up up] $($x)*) };
+    (@split 0101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up down] $($x)*) };
+    (@split 0110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down up] $($x)*) };
+    (@split 0111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down down] $($x)*) };
+    (@split 1000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up up] $($x)*) };
+    (@split 1001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up down] $($x)*) };
+    (@split 1010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down up] $($x)*) };
+    (@split 1011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down down] $($x)*) };
+    (@split 1100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up up] $($x)*) };
+    (@split 1101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up down] $($x)*) };
+    (@split 1110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down up] $($x)*) };
+    (@split 1111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down down] $($x)*) };
+    (@split 00000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up up up] $($x)*) };
+    (@split 00001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up up down] $($x)*) };
+    (@split 00010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up down up] $($x)*) };
+    (@split 00011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up down down] $($x)*) };
+    (@split 00100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down up up] $($x)*) };
+    (@split 00101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down up down] $($x)*) };
+    (@split 00110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down down up] $($x)*) };
+    (@split 00111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down down down] $($x)*) };
+    (@split 01000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up up up] $($x)*) };
+    (@split 01001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up up down] $($x)*) };
+    (@split 01010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up down up] $($x)*) };
+    (@split 01011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up down down] $($x)*) };
+    (@split 01100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down up up] $($x)*) };
+    (@split 01101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down up down] $($x)*) };
+    (@split 01110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down down up] $($x)*) };
+    (@split 01111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down down down] $($x)*) };
+    (@split 10000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up up up] $($x)*) };
+    (@split 10001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up up down] $($x)*) };
+    (@split 10010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up down up] $($x)*) };
+    (@split 10011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up down down] $($x)*) };
+    (@split 10100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down up up] $($x)*) };
+    (@split 10101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down up down] $($x)*) };
+    (@split 10110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down down up] $($x)*) };
+    (@split 10111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down down down] $($x)*) };
+    (@split 11000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up up up] $($x)*) };
+    (@split 11001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up up down] $($x)*) };
+    (@split 11010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up down up] $($x)*) };
+    (@split 11011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up down down] $($x)*) };
+    (@split 11100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down up up] $($x)*) };
+    (@split 11101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down up down] $($x)*) };
+    (@split 11110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down down up] $($x)*) };
+    (@split 11111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down down down] $($x)*) };
+    (@split 000000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up up up up] $($x)*) };
+    (@split 000001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up up up down] $($x)*) };
+    (@split 000010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up up down up] $($x)*) };
+    (@split 000011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up up down down] $($x)*) };
+    (@split 000100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up down up up] $($x)*) };
+    (@split 000101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up down up down] $($x)*) };
+    (@split 000110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up down down up] $($x)*) };
+    (@split 000111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up up down down down] $($x)*) };
+    (@split 001000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down up up up] $($x)*) };
+    (@split 001001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down up up down] $($x)*) };
+    (@split 001010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down up down up] $($x)*) };
+    (@split 001011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down up down down] $($x)*) };
+    (@split 001100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down down up up] $($x)*) };
+    (@split 001101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down down up down] $($x)*) };
+    (@split 001110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down down down up] $($x)*) };
+    (@split 001111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up up down down down down] $($x)*) };
+    (@split 010000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up up up up] $($x)*) };
+    (@split 010001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up up up down] $($x)*) };
+    (@split 010010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up up down up] $($x)*) };
+    (@split 010011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up up down down] $($x)*) };
+    (@split 010100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up down up up] $($x)*) };
+    (@split 010101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up down up down] $($x)*) };
+    (@split 010110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up down down up] $($x)*) };
+    (@split 010111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down up down down down] $($x)*) };
+    (@split 011000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down up up up] $($x)*) };
+    (@split 011001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down up up down] $($x)*) };
+    (@split 011010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down up down up] $($x)*) };
+    (@split 011011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down up down down] $($x)*) };
+    (@split 011100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down down up up] $($x)*) };
+    (@split 011101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down down up down] $($x)*) };
+    (@split 011110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down down down up] $($x)*) };
+    (@split 011111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* up down down down down down] $($x)*) };
+    (@split 100000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up up up up] $($x)*) };
+    (@split 100001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up up up down] $($x)*) };
+    (@split 100010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up up down up] $($x)*) };
+    (@split 100011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up up down down] $($x)*) };
+    (@split 100100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up down up up] $($x)*) };
+    (@split 100101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up down up down] $($x)*) };
+    (@split 100110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up down down up] $($x)*) };
+    (@split 100111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up up down down down] $($x)*) };
+    (@split 101000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down up up up] $($x)*) };
+    (@split 101001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down up up down] $($x)*) };
+    (@split 101010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down up down up] $($x)*) };
+    (@split 101011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down up down down] $($x)*) };
+    (@split 101100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down down up up] $($x)*) };
+    (@split 101101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down down up down] $($x)*) };
+    (@split 101110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down down down up] $($x)*) };
+    (@split 101111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down up down down down down] $($x)*) };
+    (@split 110000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up up up up] $($x)*) };
+    (@split 110001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up up up down] $($x)*) };
+    (@split 110010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up up down up] $($x)*) };
+    (@split 110011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up up down down] $($x)*) };
+    (@split 110100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up down up up] $($x)*) };
+    (@split 110101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up down up down] $($x)*) };
+    (@split 110110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up down down up] $($x)*) };
+    (@split 110111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down up down down down] $($x)*) };
+    (@split 111000 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down up up up] $($x)*) };
+    (@split 111001 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down up up down] $($x)*) };
+    (@split 111010 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down up down up] $($x)*) };
+    (@split 111011 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down up down down] $($x)*) };
+    (@split 111100 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down down up up] $($x)*) };
+    (@split 111101 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down down up down] $($x)*) };
+    (@split 111110 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down down down up] $($x)*) };
+    (@split 111111 @$state:ident [$($s:ident)*] $($x:tt)*) => { $crate::__dirac!(@$state [$($s)* down down down down down down] $($x)*) };
+    (@split $bits:tt @$state:ident $($x:tt)*) => {
+        compile_error!(concat!("invalid label symbol `", stringify!($bits), "`, expected 0, 1, + or -, with runs of more than 6 digits split by spaces"))
+    };
+}
@@ -0,0 +1,97 @@
+// Helpers generating owned and borrowed variants of arithmetic operators.
+
+/// Binary operator between two bra-ket objects.
+///
+/// The body is written once against borrowed operands; owned and mixed variants forward to it.
+macro_rules! impl_binop {
+    (
+        [$($gen:tt)*] $Op:ident, $op:ident for $Lhs:ty, $Rhs:ty => $Out:ty
+        where [$($bounds:tt)*]
+        |$a:ident, $b:ident| $body:expr
+    ) => {
+        impl<'a, 'b, $($gen)*> ::std::ops::$Op<&'b $Rhs> for &'a $Lhs where $($bounds)* {
+            type Output = $Out;
+
+            fn $op(self, other: &'b $Rhs) -> Self::Output {
+                let ($a, $b) = (self, other);
+                $body
+            }
+        }
+
+        impl<'a, $($gen)*> ::std::ops::$Op<$Rhs> for &'a $Lhs where $($bounds)* {
+            type Output = $Out;
+
+            fn $op(self, other: $Rhs) -> Self::Output {
+                ::std::ops::$Op::$op(self, &other)
+            }
+        }
+
+        impl<'b, $($gen)*> ::std::ops::$Op<&'b $Rhs> for $Lhs where $($bounds)* {
+            type Output = $Out;
+
+            fn $op(self, other: &'b $Rhs) -> Self::Output {
+                ::std::ops::$Op::$op(&self, other)
+            }
+        }
+
+        impl<$($gen)*> ::std::ops::$Op<$Rhs> for $Lhs where $($bounds)* {
+            type Output = $Out;
+
+            fn $op(self, other: $Rhs) -> Self::Output {
+                ::std::ops::$Op::$op(&self, &other)
+            }
+        }
+    };
+}
+
+/// Binary operator between a bra-ket object and a (copyable) scalar.
+macro_rules! impl_scalar_op {
+    (
+        [$($gen:tt)*] $Op:ident, $op:ident for $Lhs:ty, $Rhs:ty => $Out:ty
+        where [$($bounds:tt)*]
+        |$a:ident, $b:ident| $body:expr
+    ) => {
+        impl<'a, $($gen)*> ::std::ops::$Op<$Rhs> for &'a $Lhs where $($bounds)* {
+            type Output = $Out;
+
+            fn $op(self, other: $Rhs) -> Self::Output {
+                let ($a, $b) = (self, other);
+                $body
+            }
+        }
+
+        impl<$($gen)*> ::std::ops::$Op<$Rhs> for $Lhs where $($bounds)* {
+            type Output = $Out;
+
+            fn $op(self, other: $Rhs) -> Self::Output {
+                ::std::ops::$Op::$op(&self, other)
+            }
+        }
+    };
+}
+
+/// Negation of a bra-ket object.
+macro_rules! impl_neg {
+    (
+        [$($gen:tt)*] for $T:ty
+        where [$($bounds:tt)*]
+        |$a:ident| $body:expr
+    ) => {
+        impl<'a, $($gen)*> ::std::ops::Neg for &'a $T where $($bounds)* {
+            type Output = $T;
+
+            fn neg(self) -> Self::Output {
+                let $a = self;
+                $body
+            }
+        }
+
+        impl<$($gen)*> ::std::ops::Neg for $T where $($bounds)* {
+            type Output = $T;
+
+            fn neg(self) -> Self::Output {
+                -&self
+            }
+        }
+    };
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use nalgebra::{DefaultAllocator, DimName};
+use nalgebra::allocator::Allocator;
+use rand::Rng;
+
+use {linalg, Basis, DKet, Ket, Complex};
+
+/// Label of the basis state: bit string for qubit registers, index otherwise.
+///
+/// Qubit 0 is the leftmost (most significant) bit.
+pub(crate) fn basis_label(index: usize, dim: usize) -> String {
+    match linalg::qubit_count(dim) {
+        Ok(n) if n > 0 => format!("{:0width$b}", index, width = n),
+        _ => format!("{}", index),
+    }
+}
+
+/// Probabilities of the basis outcomes, normalized by the norm of the state.
+pub(crate) fn probabilities(amplitudes: &[Complex]) -> Vec<f64> {
+    let total: f64 = amplitudes.iter().map(|a| a.norm_sqr()).sum();
+    amplitudes.iter().map(|a| a.norm_sqr() / total).collect()
+}
+
+/// Pick an outcome index according to the distribution.
+pub(crate) fn pick<R: Rng>(distribution: &[f64], rng: &mut R) -> usize {
+    let threshold: f64 = rng.gen();
+    let mut cumulative = 0.0;
+    for (i, p) in distribution.iter().enumerate() {
+        cumulative += p;
+        if threshold < cumulative {
+            return i;
+        }
+    }
+    // rounding leftovers go to the last possible outcome
+    distribution.iter().rposition(|&p| p > 0.0).unwrap_or(0)
+}
+
+// Histogram of `shots` outcomes drawn from the distribution, labeled with `basis_label`
+fn histogram<R: Rng>(probabilities: &[f64], shots: usize, rng: &mut R) -> BTreeMap<String, usize> {
+    let mut histogram = BTreeMap::new();
+    for _ in 0..shots {
+        *histogram.entry(basis_label(pick(probabilities, rng), probabilities.len())).or_insert(0) += 1;
+    }
+    histogram
+}
+
+impl<D: DimName> Ket<D>
+    where DefaultAllocator: Allocator<Complex, D>
+{
+    /// Probabilities of all outcomes of the measurement in the computational basis
+    pub fn probabilities(&self) -> Vec<f64> {
+        probabilities(self.0.as_slice())
+    }
+
+    /// Measure the state in the computational basis
+    ///
+    /// Returns the outcome (index of the basis state) and the normalized post-measurement state.
+    pub fn measure<R: Rng>(&self, rng: &mut R) -> (usize, Ket<D>) {
+        let outcome = pick(&self.probabilities(), rng);
+
+        let mut collapsed = self.clone();
+        for (i, a) in collapsed.0.iter_mut().enumerate() {
+            *a = if i == outcome { *a / a.norm() } else { 0.0.into() };
+        }
+
+        (outcome, collapsed)
+    }
+
+    /// Measure a single qubit of the register in the computational basis
+    ///
+    /// Returns the outcome (0 or 1) and the normalized post-measurement state of the whole register.
+    pub fn measure_qubit<R: Rng>(&self, qubit: usize, rng: &mut R) -> Result<(usize, Ket<D>), &'static str> {
+        let n = linalg::qubit_count(D::name().value())?;
+        if qubit >= n {
+            return Err("Qubit index is out of the register range");
+        }
+        let mask = 1 << (n - 1 - qubit);
+
+        let probabilities = self.probabilities();
+        let one: f64 = probabilities.iter().enumerate().filter(|&(i, _)| i & mask != 0).map(|(_, p)| p).sum();
+        let outcome = pick(&[1.0 - one, one], rng);
+
+        let mut collapsed = self.clone();
+        for (i, a) in collapsed.0.iter_mut().enumerate() {
+            if (i & mask != 0) != (outcome == 1) {
+                *a = 0.0.into();
+            }
+        }
+        let norm = collapsed.0.norm();
+        collapsed.0 /= Complex::from(norm);
+
+        Ok((outcome, collapsed))
+    }
+
+    /// Measure `shots` copies of the state, returning how many times each basis state was observed
+    ///
+    /// Basis states of qubit registers are labeled with bit strings (qubit 0 is the leftmost bit),
+    /// up being `0`. Note that `Ket::from_bits` maps set bits to up, i.e. to `0` in the label.
+    pub fn sample<R: Rng>(&self, shots: usize, rng: &mut R) -> BTreeMap<String, usize> {
+        histogram(&self.probabilities(), shots, rng)
+    }
+
+    /// Probabilities of all outcomes of the measurement in the given basis
+    pub fn probabilities_in(&self, basis: &Basis<D>) -> Vec<f64> {
+        probabilities(basis.coordinates(self).0.as_slice())
+    }
+
+    /// Measure the state in the given basis
+    ///
+    /// Returns the outcome (index of the basis ket) and the post-measurement state, which is
+    /// the basis ket up to a phase.
+    pub fn measure_in<R: Rng>(&self, basis: &Basis<D>, rng: &mut R) -> (usize, Ket<D>) {
+        let outcome = pick(&self.probabilities_in(basis), rng);
+
+        let ket = &basis.kets()[outcome];
+        let amplitude = ket.0.dotc(&self.0);
+
+        (outcome, ket * (amplitude / amplitude.norm()))
+    }
+}
+
+impl DKet {
+    /// Probabilities of all outcomes of the measurement in the computational basis
+    pub fn probabilities(&self) -> Vec<f64> {
+        probabilities(self.0.as_slice())
+    }
+
+    /// Measure `shots` copies of the state, returning how many times each basis state was observed
+    ///
+    /// Same labels as `Ket::sample`.
+    pub fn sample<R: Rng>(&self, shots: usize, rng: &mut R) -> BTreeMap<String, usize> {
+        histogram(&self.probabilities(), shots, rng)
+    }
+}
@@ -1,8 +1,7 @@
-use std::ops::{Mul, Add};
-use nalgebra::{DefaultAllocator, MatrixMN, DimName, U2, U4, Matrix2, Matrix4, Matrix};
+use nalgebra::{DefaultAllocator, MatrixMN, DimName, U2, U4, U8, Matrix2, Matrix4, Matrix, DimNameProd, DimNameMul};
 use nalgebra::allocator::Allocator;
 
-use {Ket, Complex, SQRT_2_INVERSE};
+use {dirac, linalg, Ket, Complex, SQRT_2_INVERSE};
 
 /// Outer product in bra-ket notation, as well used as a linear operatior.
 /// You can multiply it by ket to put it in the observable state defined by the operator.
@@ -10,34 +9,43 @@ use {Ket, Complex, SQRT_2_INVERSE};
 pub struct Outer<D: DimName>(pub(crate) MatrixMN<Complex, D, D>)
     where DefaultAllocator: Allocator<Complex, D, D>;
 
-impl<D: DimName> Mul<Ket<D>> for Outer<D>
-    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D>
-{
-    type Output = Ket<D>;
+impl_binop!(
+    [D: DimName] Mul, mul for Outer<D>, Ket<D> => Ket<D>
+    where [DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D>]
+    |outer, ket| Ket(&outer.0 * &ket.0)
+);
 
-    fn mul(self, other: Ket<D>) -> Self::Output {
-        Ket(self.0 * other.0)
-    }
-}
+impl_binop!(
+    [D: DimName] Mul, mul for Outer<D>, Outer<D> => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |a, b| Outer(&a.0 * &b.0)
+);
 
 impl<D: DimName> ::std::fmt::Display for Outer<D>
-    where DefaultAllocator: Allocator<Complex, D, D>,
-    DefaultAllocator: Allocator<usize, D, D>
+    where DefaultAllocator: Allocator<Complex, D, D>
 {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        write!(f, "{}", self.0)
+        f.write_str(&self.to_dirac(&dirac::formatter_options(f)))
     }
 }
 
-impl<D: DimName> Add<Outer<D>> for Outer<D>
-    where DefaultAllocator: Allocator<Complex, D, D>
-{
-    type Output = Self;
+impl_binop!(
+    [D: DimName] Add, add for Outer<D>, Outer<D> => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |a, b| Outer(&a.0 + &b.0)
+);
 
-    fn add(self, other: Outer<D>) -> Self::Output {
-        Outer(self.0 + other.0)
-    }
-}
+impl_binop!(
+    [D: DimName] Sub, sub for Outer<D>, Outer<D> => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |a, b| Outer(&a.0 - &b.0)
+);
+
+impl_neg!(
+    [D: DimName] for Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |a| Outer(-&a.0)
+);
 
 impl<D: DimName> Outer<D>
     where DefaultAllocator: Allocator<Complex, D, D>
@@ -47,6 +55,200 @@ impl<D: DimName> Outer<D>
         self.0
     }
 
+    /// Hermitian conjugate (adjoint) operator, A†
+    pub fn dagger(&self) -> Outer<D> {
+        Outer(self.0.adjoint())
+    }
+
+    /// Trace of the operator, Tr(A)
+    pub fn trace(&self) -> Complex {
+        self.0.trace()
+    }
+
+    /// Determinant of the operator, det A
+    pub fn determinant(&self) -> Complex {
+        linalg::to_dynamic(&self.0).determinant()
+    }
+
+    /// Integer power of the operator, A^n (A^0 is identity)
+    pub fn pow(&self, n: u32) -> Outer<D> {
+        let mut result = Outer::identity();
+        let mut base = self.clone();
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            n >>= 1;
+        }
+        result
+    }
+
+    /// Operator exponent, e^A
+    ///
+    /// Computed through eigendecomposition, so the operator should be normal
+    /// (hermitian, unitary, etc.), e.g. `(H * Complex::new(0.0, -t)).exp()` for evolution.
+    pub fn exp(&self) -> Outer<D> {
+        self.map_eigenvalues(|v| v.exp())
+    }
+
+    /// Principal square root of the operator, √A
+    ///
+    /// Computed through eigendecomposition, so the operator should be normal.
+    pub fn sqrt(&self) -> Outer<D> {
+        self.map_eigenvalues(|v| v.sqrt())
+    }
+
+    /// Principal logarithm of the operator, log A
+    ///
+    /// Computed through eigendecomposition, so the operator should be normal and non-singular.
+    pub fn log(&self) -> Outer<D> {
+        self.map_eigenvalues(|v| v.ln())
+    }
+
+    fn map_eigenvalues<F: Fn(Complex) -> Complex>(&self, f: F) -> Outer<D> {
+        Outer(linalg::from_dynamic(&linalg::map_normal(linalg::to_dynamic(&self.0), f)))
+    }
+
+    /// Identity operator
+    pub fn identity() -> Outer<D> {
+        Outer(MatrixMN::identity_generic(D::name(), D::name()))
+    }
+
+    /// Kronecker (tensor) product of operators of arbitrary dimensions
+    ///
+    /// `A ⊗ B`, where `self` acts on the most significant subsystem.
+    pub fn tensor<B: DimName>(self, other: Outer<B>) -> Outer<DimNameProd<D, B>>
+        where
+            D: DimNameMul<B>,
+            DefaultAllocator: Allocator<Complex, B, B> + Allocator<Complex, DimNameProd<D, B>, DimNameProd<D, B>>
+    {
+        let dim = DimNameProd::<D, B>::name();
+        let mut result: MatrixMN<Complex, DimNameProd<D, B>, DimNameProd<D, B>> = Matrix::zeros_generic(dim, dim);
+        let dim_a = D::name().value();
+        let dim_b = B::name().value();
+        for i1 in 0..dim_a {
+            for j1 in 0..dim_a {
+                let coef = self.0[(i1, j1)];
+                for i2 in 0..dim_b {
+                    for j2 in 0..dim_b {
+                        *result.get_mut(
+                            (i1*dim_b + i2, j1*dim_b + j2)
+                        ).expect("(i, j) in (dim, dim) range") = coef * other.0[(i2, j2)];
+                    }
+                }
+            }
+        }
+        Outer(result)
+    }
+
+    /// Embed the operator acting on `qubits` into the register of `N` dimension.
+    ///
+    /// Qubit 0 is the most significant (leftmost) one, and `qubits[0]` is mapped to the most
+    /// significant qubit of the operator. Remaining qubits are left untouched (identity).
+    pub fn on_qubits<N: DimName>(&self, qubits: &[usize]) -> Result<Outer<N>, &'static str>
+        where DefaultAllocator: Allocator<Complex, N, N>
+    {
+        let dim = N::name().value();
+        let mut result: MatrixMN<Complex, N, N> = Matrix::zeros_generic(N::name(), N::name());
+        let mut column = vec![Complex::from(0.0); dim];
+
+        for j in 0..dim {
+            for (i, c) in column.iter_mut().enumerate() {
+                *c = if i == j { 1.0.into() } else { 0.0.into() };
+            }
+            linalg::apply_gate(&mut column, self.0.as_slice(), qubits)?;
+            result.column_mut(j).copy_from_slice(&column);
+        }
+
+        Ok(Outer(result))
+    }
+
+    /// Embed the single qubit operator acting on `qubit` into the register of `N` dimension.
+    ///
+    /// E.g. `Outer2::h2().on::<U8>(1)` is `I ⊗ H ⊗ I`.
+    pub fn on<N: DimName>(&self, qubit: usize) -> Result<Outer<N>, &'static str>
+        where DefaultAllocator: Allocator<Complex, N, N>
+    {
+        self.on_qubits(&[qubit])
+    }
+
+    /// Embed the two qubit operator acting on `first` and `second` qubits (e.g. control and target)
+    /// into the register of `N` dimension.
+    pub fn on_pair<N: DimName>(&self, first: usize, second: usize) -> Result<Outer<N>, &'static str>
+        where DefaultAllocator: Allocator<Complex, N, N>
+    {
+        self.on_qubits(&[first, second])
+    }
+
+    /// Controlled operator, `|0><0| ⊗ I + |1><1| ⊗ U`, with the control as the most significant qubit
+    pub fn controlled(&self) -> Outer<DimNameProd<U2, D>>
+        where
+            U2: DimNameMul<D>,
+            DefaultAllocator: Allocator<Complex, DimNameProd<U2, D>, DimNameProd<U2, D>>
+    {
+        self.controlled_on(&[true]).expect("U2 * D always fits one control qubit")
+    }
+
+    /// Operator controlled by `n_controls` most significant qubits in the register of `N` dimension
+    ///
+    /// Applies only when all control qubits are |1>.
+    pub fn controlled_by<N: DimName>(&self, n_controls: usize) -> Result<Outer<N>, &'static str>
+        where DefaultAllocator: Allocator<Complex, N, N>
+    {
+        self.controlled_on(&vec![true; n_controls])
+    }
+
+    /// Operator controlled by the most significant qubits in the register of `N` dimension
+    ///
+    /// `true` stands for the regular control (applies on |1>), `false` for the negative control
+    /// (applies on |0>).
+    pub fn controlled_on<N: DimName>(&self, controls: &[bool]) -> Result<Outer<N>, &'static str>
+        where DefaultAllocator: Allocator<Complex, N, N>
+    {
+        let dim = D::name().value();
+        if controls.len() >= 8 * ::std::mem::size_of::<usize>() || N::name().value() != dim << controls.len() {
+            return Err("Register dimension should be 2^controls * operator dimension");
+        }
+
+        let block = controls.iter().fold(0, |acc, &c| (acc << 1) | c as usize);
+        let offset = block * dim;
+
+        let mut result: MatrixMN<Complex, N, N> = Matrix::identity_generic(N::name(), N::name());
+        for i in 0..dim {
+            for j in 0..dim {
+                *result.get_mut((offset + i, offset + j)).expect("(i, j) in (dim, dim) range") = self.0[(i, j)];
+            }
+        }
+
+        Ok(Outer(result))
+    }
+
+    /// Partial trace over all qubits of the register except `keep`
+    ///
+    /// Kept qubits appear in the result in the order given.
+    pub fn partial_trace<K: DimName>(&self, keep: &[usize]) -> Result<Outer<K>, &'static str>
+        where DefaultAllocator: Allocator<Complex, K, K>
+    {
+        let n = linalg::qubit_count(D::name().value())?;
+        self.partial_trace_subsystems(&vec![2; n], keep)
+    }
+
+    /// Partial trace over all subsystems except `keep`, for the operator acting on the tensor
+    /// product of subsystems of `dims` dimensions (subsystem 0 is the most significant)
+    ///
+    /// Kept subsystems appear in the result in the order given.
+    pub fn partial_trace_subsystems<K: DimName>(&self, dims: &[usize], keep: &[usize]) -> Result<Outer<K>, &'static str>
+        where DefaultAllocator: Allocator<Complex, K, K>
+    {
+        let reduced = linalg::partial_trace(&linalg::to_dynamic(&self.0), dims, keep)?;
+        if reduced.nrows() != K::name().value() {
+            return Err("Result dimension does not match the kept subsystems");
+        }
+        Ok(Outer(linalg::from_dynamic(&reduced)))
+    }
+
     /// H2 (2 dim hadamard) operator
     pub fn h2() -> Outer<U2> {
         Outer::<U2>(Matrix2::<Complex>::new(1.0.into(), 1.0.into(), 1.0.into(), (-1.0).into())) * SQRT_2_INVERSE
@@ -74,6 +276,165 @@ impl<D: DimName> Outer<D>
         )
     }
 
+    /// Y2 (2 dim Pauli Y-gate) operator
+    pub fn y2() -> Outer<U2> {
+        Outer::<U2>(Matrix2::<Complex>::new(0.0.into(), -Complex::i(), Complex::i(), 0.0.into()))
+    }
+
+    /// S2 (2 dim phase √Z gate) operator
+    pub fn s2() -> Outer<U2> {
+        Outer::<U2>(Matrix2::<Complex>::new(1.0.into(), 0.0.into(), 0.0.into(), Complex::i()))
+    }
+
+    /// S2† (2 dim inverse phase gate) operator
+    pub fn sdg2() -> Outer<U2> {
+        Outer::<U2>(Matrix2::<Complex>::new(1.0.into(), 0.0.into(), 0.0.into(), -Complex::i()))
+    }
+
+    /// T2 (2 dim π/8 gate) operator
+    pub fn t2() -> Outer<U2> {
+        Outer::<U2>::p2(::std::f64::consts::FRAC_PI_4)
+    }
+
+    /// T2† (2 dim inverse π/8 gate) operator
+    pub fn tdg2() -> Outer<U2> {
+        Outer::<U2>::p2(-::std::f64::consts::FRAC_PI_4)
+    }
+
+    /// √X2 (2 dim square root of N-gate) operator
+    pub fn sx2() -> Outer<U2> {
+        let p = Complex::new(0.5, 0.5);
+        let m = Complex::new(0.5, -0.5);
+        Outer::<U2>(Matrix2::<Complex>::new(p, m, m, p))
+    }
+
+    /// P2 (2 dim arbitrary phase gate) operator, diag(1, e^iφ)
+    pub fn p2(phi: f64) -> Outer<U2> {
+        Outer::<U2>(Matrix2::<Complex>::new(1.0.into(), 0.0.into(), 0.0.into(), Complex::from_polar(&1.0, &phi)))
+    }
+
+    /// Rx2 (2 dim rotation around X axis by θ) operator
+    pub fn rx2(theta: f64) -> Outer<U2> {
+        let (sin, cos) = (theta / 2.0).sin_cos();
+        Outer::<U2>(
+            Matrix2::<Complex>::new(
+                cos.into(), Complex::new(0.0, -sin),
+                Complex::new(0.0, -sin), cos.into(),
+            )
+        )
+    }
+
+    /// Ry2 (2 dim rotation around Y axis by θ) operator
+    pub fn ry2(theta: f64) -> Outer<U2> {
+        let (sin, cos) = (theta / 2.0).sin_cos();
+        Outer::<U2>(Matrix2::<Complex>::new(cos.into(), (-sin).into(), sin.into(), cos.into()))
+    }
+
+    /// Rz2 (2 dim rotation around Z axis by θ) operator
+    pub fn rz2(theta: f64) -> Outer<U2> {
+        Outer::<U2>(
+            Matrix2::<Complex>::new(
+                Complex::from_polar(&1.0, &(-theta / 2.0)), 0.0.into(),
+                0.0.into(), Complex::from_polar(&1.0, &(theta / 2.0)),
+            )
+        )
+    }
+
+    /// U3 (2 dim generic single qubit rotation with Euler angles θ, φ, λ) operator
+    pub fn u3(theta: f64, phi: f64, lambda: f64) -> Outer<U2> {
+        let (sin, cos) = (theta / 2.0).sin_cos();
+        Outer::<U2>(
+            Matrix2::<Complex>::new(
+                cos.into(), -Complex::from_polar(&sin, &lambda),
+                Complex::from_polar(&sin, &phi), Complex::from_polar(&cos, &(phi + lambda)),
+            )
+        )
+    }
+
+    /// CZ (2*2 dim controlled Z-gate) operator
+    pub fn cz() -> Outer<U4> {
+        Outer::<U4>(
+            Matrix4::<Complex>::new(
+                1.0.into(), 0.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 1.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 1.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 0.0.into(), (-1.0).into(),
+            )
+        )
+    }
+
+    /// CY (2*2 dim controlled Y-gate) operator
+    pub fn cy() -> Outer<U4> {
+        Outer::<U4>(
+            Matrix4::<Complex>::new(
+                1.0.into(), 0.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 1.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 0.0.into(), -Complex::i(),
+                0.0.into(), 0.0.into(), Complex::i(), 0.0.into(),
+            )
+        )
+    }
+
+    /// SWAP (2*2 dim) operator
+    pub fn swap() -> Outer<U4> {
+        Outer::<U4>(
+            Matrix4::<Complex>::new(
+                1.0.into(), 0.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 1.0.into(), 0.0.into(),
+                0.0.into(), 1.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 0.0.into(), 1.0.into(),
+            )
+        )
+    }
+
+    /// iSWAP (2*2 dim) operator
+    pub fn iswap() -> Outer<U4> {
+        Outer::<U4>(
+            Matrix4::<Complex>::new(
+                1.0.into(), 0.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), Complex::i(), 0.0.into(),
+                0.0.into(), Complex::i(), 0.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 0.0.into(), 1.0.into(),
+            )
+        )
+    }
+
+    /// √SWAP (2*2 dim) operator
+    pub fn sqrt_swap() -> Outer<U4> {
+        let p = Complex::new(0.5, 0.5);
+        let m = Complex::new(0.5, -0.5);
+        Outer::<U4>(
+            Matrix4::<Complex>::new(
+                1.0.into(), 0.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), p, m, 0.0.into(),
+                0.0.into(), m, p, 0.0.into(),
+                0.0.into(), 0.0.into(), 0.0.into(), 1.0.into(),
+            )
+        )
+    }
+
+    /// Controlled phase (2*2 dim) operator, diag(1, 1, 1, e^iφ)
+    pub fn cphase(phi: f64) -> Outer<U4> {
+        Outer::<U4>(
+            Matrix4::<Complex>::new(
+                1.0.into(), 0.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 1.0.into(), 0.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 1.0.into(), 0.0.into(),
+                0.0.into(), 0.0.into(), 0.0.into(), Complex::from_polar(&1.0, &phi),
+            )
+        )
+    }
+
+    /// Toffoli (CCNOT, 2*2*2 dim) operator
+    pub fn toffoli() -> Outer<U8> {
+        permutation8(6, 7)
+    }
+
+    /// Fredkin (CSWAP, 2*2*2 dim) operator
+    pub fn fredkin() -> Outer<U8> {
+        permutation8(5, 6)
+    }
+
     /// Quantum Fourier Transform (QFT) matrix operator
     pub fn qft() -> Outer<D> {
         let mut matrix: MatrixMN<Complex, D, D> = Matrix::zeros_generic(D::name(), D::name());
@@ -81,7 +442,7 @@ impl<D: DimName> Outer<D>
 
         let n = dim as f64;
 
-        let coef = (Complex::from(1.0) / (n as f64)).sqrt();
+        let coef = (Complex::from(1.0) / n).sqrt();
 
         for i in 0..dim {
             for j in 0..dim {
@@ -96,6 +457,13 @@ impl<D: DimName> Outer<D>
     }
 }
 
+// 3 qubit identity with two basis states swapped
+fn permutation8(a: usize, b: usize) -> Outer<U8> {
+    let mut matrix = MatrixMN::<Complex, U8, U8>::identity();
+    matrix.swap_rows(a, b);
+    Outer(matrix)
+}
+
 impl<D: DimName> From<MatrixMN<Complex, D, D>> for Outer<D>
     where DefaultAllocator: Allocator<Complex, D, D>
 {
@@ -104,12 +472,26 @@ impl<D: DimName> From<MatrixMN<Complex, D, D>> for Outer<D>
     }
 }
 
-impl<D: DimName> Mul<f64> for Outer<D>
-    where DefaultAllocator: Allocator<Complex, D, D>
-{
-    type Output = Self;
+impl_scalar_op!(
+    [D: DimName] Mul, mul for Outer<D>, f64 => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |outer, scalar| Outer(&outer.0 * Complex::new(scalar, 0.0))
+);
 
-    fn mul(self, other: f64) -> Self::Output {
-        Outer(self.0 * Complex::new(other, 0.0))
-    }
-}
\ No newline at end of file
+impl_scalar_op!(
+    [D: DimName] Mul, mul for Outer<D>, Complex => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |outer, scalar| Outer(&outer.0 * scalar)
+);
+
+impl_scalar_op!(
+    [D: DimName] Div, div for Outer<D>, f64 => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |outer, scalar| Outer(&outer.0 / Complex::new(scalar, 0.0))
+);
+
+impl_scalar_op!(
+    [D: DimName] Div, div for Outer<D>, Complex => Outer<D>
+    where [DefaultAllocator: Allocator<Complex, D, D>]
+    |outer, scalar| Outer(&outer.0 / scalar)
+);
@@ -0,0 +1,271 @@
+use super::{is_label, ErrorKind, ParseError, Span, LABEL_SYMBOLS, MAX_LABEL_QUBITS};
+use super::lexer::Token;
+use Complex;
+
+/// Unary operation of the expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// Negation, `-a`
+    Neg,
+    /// Hermitian conjugate, `a†`
+    Dagger,
+}
+
+/// Binary operation of the expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    /// Sum, `a + b`
+    Add,
+    /// Difference, `a - b`
+    Sub,
+    /// Product, `a * b` or juxtaposition `a b`
+    Mul,
+    /// Division by a scalar, `a / b`
+    Div,
+    /// Tensor product, `a ⊗ b`
+    Tensor,
+}
+
+/// Scalar function of the expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Function {
+    /// Principal square root, `sqrt(a)`
+    Sqrt,
+    /// Exponent, `exp(a)`
+    Exp,
+}
+
+/// Node of the expression syntax tree.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    /// Complex number literal, `0.5`, `2i` or `i`
+    Number(Complex),
+    /// Ket with the label, `|01>`
+    Ket(String),
+    /// Bra with the label, `<01|`
+    Bra(String),
+    /// Named value, e.g. an operator `H`
+    Name(String),
+    /// Named ket, `|ψ>`
+    NamedKet(String),
+    /// Bra of the named ket, `<ψ|`
+    NamedBra(String),
+    /// Unary operation
+    Unary(UnaryOp, Box<Expr>),
+    /// Binary operation
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// Function call
+    Call(Function, Box<Expr>),
+}
+
+/// Expression with the byte span of its source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    /// Syntax tree node
+    pub kind: ExprKind,
+    /// Source of the node in the input
+    pub span: Span,
+}
+
+impl Expr {
+    fn new(kind: ExprKind, span: Span) -> Expr {
+        Expr { kind, span }
+    }
+
+    fn binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+        let span = left.span.join(right.span);
+        Expr::new(ExprKind::Binary(op, Box::new(left), Box::new(right)), span)
+    }
+}
+
+// Whether the label is a name rather than a sequence of basis symbols
+fn is_name(label: &str) -> bool {
+    label.starts_with(char::is_alphabetic)
+        && label.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !is_label(label)
+}
+
+// Check the label of the bra or ket spanning `span` (brackets included)
+fn check_label(label: &str, span: Span) -> Result<(), ParseError> {
+    if label.is_empty() {
+        return Err(ParseError::new(ErrorKind::EmptyLabel, span));
+    }
+    match label.char_indices().find(|&(_, c)| !LABEL_SYMBOLS.contains(c)) {
+        Some((offset, c)) => {
+            let start = span.start + 1 + offset;
+            Err(ParseError::new(ErrorKind::UnknownLabel(c), Span::new(start, start + c.len_utf8())))
+        },
+        None if label.len() > MAX_LABEL_QUBITS => Err(ParseError::new(ErrorKind::LabelTooLong, span)),
+        None => Ok(()),
+    }
+}
+
+/// Recursive descent parser over the tokens.
+pub(crate) struct Parser<'a> {
+    tokens: &'a [(Token, Span)],
+    position: usize,
+    input_length: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(tokens: &'a [(Token, Span)], input_length: usize) -> Parser<'a> {
+        Parser { tokens, position: 0, input_length }
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Result<&'a (Token, Span), ParseError> {
+        let end = Span::new(self.input_length, self.input_length);
+        let token = self.tokens.get(self.position).ok_or_else(|| ParseError::new(ErrorKind::UnexpectedEnd, end))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    pub(crate) fn parse(mut self) -> Result<Expr, ParseError> {
+        let expr = self.sum()?;
+        match self.tokens.get(self.position) {
+            None => Ok(expr),
+            Some(&(_, span)) => Err(ParseError::new(ErrorKind::UnexpectedToken, span)),
+        }
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Some(&Token::Plus) => BinaryOp::Add,
+                Some(&Token::Minus) => BinaryOp::Sub,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.product()?;
+            left = Expr::binary(op, left, right);
+        }
+    }
+
+    // product := tensor (('*' | '/') tensor | tensor)*, juxtaposition is a product
+    fn product(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.tensor()?;
+        loop {
+            let op = match self.peek() {
+                Some(&Token::Star) => {
+                    self.position += 1;
+                    BinaryOp::Mul
+                },
+                Some(&Token::Slash) => {
+                    self.position += 1;
+                    BinaryOp::Div
+                },
+                Some(&Token::Bra(_)) | Some(&Token::Ket(_)) | Some(&Token::Number(_)) |
+                Some(&Token::Identifier(_)) | Some(&Token::LeftParen) => BinaryOp::Mul,
+                _ => return Ok(left),
+            };
+            let right = self.tensor()?;
+            left = Expr::binary(op, left, right);
+        }
+    }
+
+    // tensor := unary ('⊗' unary)*
+    fn tensor(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.unary()?;
+        while let Some(&Token::Tensor) = self.peek() {
+            self.position += 1;
+            let right = self.unary()?;
+            left = Expr::binary(BinaryOp::Tensor, left, right);
+        }
+        Ok(left)
+    }
+
+    // unary := '-' unary | primary '†'*
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if let Some(&Token::Minus) = self.peek() {
+            let &(_, span) = self.next()?;
+            let operand = self.unary()?;
+            let span = span.join(operand.span);
+            return Ok(Expr::new(ExprKind::Unary(UnaryOp::Neg, Box::new(operand)), span));
+        }
+
+        let mut expr = self.primary()?;
+        while let Some(&(Token::Dagger, span)) = self.tokens.get(self.position) {
+            self.position += 1;
+            let span = expr.span.join(span);
+            expr = Expr::new(ExprKind::Unary(UnaryOp::Dagger, Box::new(expr)), span);
+        }
+        Ok(expr)
+    }
+
+    // primary := number | identifier | function '(' sum ')' | ket | bra | bra-ket | '(' sum ')'
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let &(ref token, span) = self.next()?;
+
+        match *token {
+            Token::Bra(_) => {
+                let bra = self.label(token, span)?;
+                // `<a|b>` sharing the bar is a single bracket
+                match self.tokens.get(self.position) {
+                    Some(&(ref token @ Token::Ket(_), ket_span)) if ket_span.start + 1 == span.end => {
+                        self.position += 1;
+                        Ok(Expr::binary(BinaryOp::Mul, bra, self.label(token, ket_span)?))
+                    },
+                    _ => Ok(bra),
+                }
+            },
+            Token::Ket(_) => self.label(token, span),
+            Token::Number(value) => Ok(Expr::new(ExprKind::Number(value), span)),
+            Token::Identifier(ref name) => match &name[..] {
+                "i" => Ok(Expr::new(ExprKind::Number(Complex::i()), span)),
+                "pi" => Ok(Expr::new(ExprKind::Number(::std::f64::consts::PI.into()), span)),
+                "sqrt" => self.call(Function::Sqrt, span),
+                "exp" => self.call(Function::Exp, span),
+                _ => Ok(Expr::new(ExprKind::Name(name.clone()), span)),
+            },
+            Token::LeftParen => {
+                let (inner, close) = self.parenthesized(span)?;
+                Ok(Expr::new(inner.kind, span.join(close)))
+            },
+            _ => Err(ParseError::new(ErrorKind::UnexpectedToken, span)),
+        }
+    }
+
+    // Bra or ket of the token, named or made of basis symbols
+    fn label(&self, token: &Token, span: Span) -> Result<Expr, ParseError> {
+        match *token {
+            Token::Ket(ref label) if is_name(label) => Ok(Expr::new(ExprKind::NamedKet(label.clone()), span)),
+            Token::Bra(ref label) if is_name(label) => Ok(Expr::new(ExprKind::NamedBra(label.clone()), span)),
+            Token::Ket(ref label) => {
+                check_label(label, span)?;
+                Ok(Expr::new(ExprKind::Ket(label.clone()), span))
+            },
+            Token::Bra(ref label) => {
+                check_label(label, span)?;
+                Ok(Expr::new(ExprKind::Bra(label.clone()), span))
+            },
+            _ => Err(ParseError::new(ErrorKind::UnexpectedToken, span)),
+        }
+    }
+
+    // Rest of the parenthesized expression after `(` at `open` and the span of the closing `)`
+    fn parenthesized(&mut self, open: Span) -> Result<(Expr, Span), ParseError> {
+        let inner = self.sum()?;
+        match self.tokens.get(self.position) {
+            Some(&(Token::RightParen, close)) => {
+                self.position += 1;
+                Ok((inner, close))
+            },
+            _ => Err(ParseError::new(ErrorKind::UnclosedParenthesis, open)),
+        }
+    }
+
+    fn call(&mut self, function: Function, name: Span) -> Result<Expr, ParseError> {
+        match *self.next()? {
+            (Token::LeftParen, open) => {
+                let (argument, close) = self.parenthesized(open)?;
+                Ok(Expr::new(ExprKind::Call(function, Box::new(argument)), name.join(close)))
+            },
+            (_, span) => Err(ParseError::new(ErrorKind::UnexpectedToken, span)),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use {DOuter, Outer, Outer2, Outer4, U8};
+use super::{eval, parse, Expr, ParseError, Value};
+
+/// Named values available in expressions, e.g. operators `H` and `CNOT` or user variables.
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    names: HashMap<String, Value>,
+}
+
+impl Context {
+    /// Context with the built-in operators: `I`, `H`, `X` (also `N`), `Y`, `Z`, `S`, `T`,
+    /// `CNOT`, `CZ`, `SWAP`, `QFT2` and `QFT3` (on 2 and 3 qubits)
+    pub fn new() -> Context {
+        let mut context = Context::empty();
+        context.register_operator("I", Outer2::identity());
+        context.register_operator("H", Outer2::h2());
+        context.register_operator("X", Outer2::n2());
+        context.register_operator("N", Outer2::n2());
+        context.register_operator("Y", Outer2::y2());
+        context.register_operator("Z", Outer2::z2());
+        context.register_operator("S", Outer2::s2());
+        context.register_operator("T", Outer2::t2());
+        context.register_operator("CNOT", Outer4::cnot());
+        context.register_operator("CZ", Outer4::cz());
+        context.register_operator("SWAP", Outer4::swap());
+        context.register_operator("QFT2", Outer4::qft());
+        context.register_operator("QFT3", Outer::<U8>::qft());
+        context
+    }
+
+    /// Context without any names
+    pub fn empty() -> Context {
+        Context { names: HashMap::new() }
+    }
+
+    /// Bind the name to the value, returning the previous value of the name if any
+    pub fn define(&mut self, name: &str, value: Value) -> Option<Value> {
+        self.names.insert(name.to_owned(), value)
+    }
+
+    /// Bind the name to the operator, returning the previous value of the name if any
+    pub fn register_operator<O: Into<DOuter>>(&mut self, name: &str, operator: O) -> Option<Value> {
+        self.define(name, Value::Outer(operator.into()))
+    }
+
+    /// Value bound to the name
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.names.get(name)
+    }
+
+    /// Evaluate the parsed expression
+    pub fn evaluate(&self, expr: &Expr) -> Result<Value, ParseError> {
+        eval::evaluate(expr, self)
+    }
+
+    /// Parse and evaluate the expression
+    pub fn evaluate_str(&self, input: &str) -> Result<Value, ParseError> {
+        parse(input).and_then(|expr| self.evaluate(&expr))
+    }
+}
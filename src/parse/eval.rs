@@ -0,0 +1,197 @@
+use dirac::{self, Notation};
+use {Bra2, DBra, DKet, DOuter, Ket2, Complex, TryAdd, TryMul};
+use super::{Context, ErrorKind, ParseError, Span, MAX_QUBITS};
+use super::ast::{BinaryOp, Expr, ExprKind, Function, UnaryOp};
+
+/// Result of evaluating an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Complex number, e.g. an inner product
+    Scalar(Complex),
+    /// Ket of the runtime dimension
+    Ket(DKet),
+    /// Bra of the runtime dimension
+    Bra(DBra),
+    /// Operator of the runtime dimension, e.g. an outer product
+    Outer(DOuter),
+}
+
+// Qubit ket of the label symbol, validated by the parser
+fn symbol_ket(symbol: char) -> Ket2 {
+    match symbol {
+        '0' | 'u' => Ket2::up(),
+        '1' | 'd' => Ket2::down(),
+        '+' | 'r' => Ket2::right(),
+        '-' | 'l' => Ket2::left(),
+        'i' => Ket2::inw(),
+        'o' => Ket2::out(),
+        _ => unreachable!("labels are checked by the parser"),
+    }
+}
+
+fn label_ket(label: &str) -> DKet {
+    label.chars().map(|c| DKet::from(symbol_ket(c)))
+        .fold(DKet::basis(1, 0).expect("0 < 1"), |acc, ket| acc.tensor(&ket))
+}
+
+fn label_bra(label: &str) -> DBra {
+    label.chars().map(|c| DBra::from(Bra2::from(symbol_ket(c))))
+        .fold(DBra::basis(1, 0).expect("0 < 1"), |acc, bra| acc.tensor(&bra))
+}
+
+fn mismatch(span: Span) -> ParseError {
+    ParseError::new(ErrorKind::InvalidOperands("dimensions do not match"), span)
+}
+
+// Check that the tensor product of the dimensions fits into `MAX_QUBITS` before building it
+fn check_tensor(a: usize, b: usize, span: Span) -> Result<(), ParseError> {
+    match a.checked_mul(b) {
+        Some(dim) if dim <= 1 << MAX_QUBITS => Ok(()),
+        _ => Err(ParseError::new(ErrorKind::InvalidOperands("result is too large"), span)),
+    }
+}
+
+// Product of juxtaposed values
+fn multiply(left: Value, right: Value, span: Span) -> Result<Value, ParseError> {
+    Ok(match (left, right) {
+        (Value::Scalar(a), Value::Scalar(b)) => Value::Scalar(a * b),
+        (Value::Scalar(a), Value::Ket(ket)) | (Value::Ket(ket), Value::Scalar(a)) => Value::Ket(ket * a),
+        (Value::Scalar(a), Value::Bra(bra)) | (Value::Bra(bra), Value::Scalar(a)) => Value::Bra(bra * a),
+        (Value::Scalar(a), Value::Outer(outer)) | (Value::Outer(outer), Value::Scalar(a)) => Value::Outer(outer * a),
+        (Value::Bra(bra), Value::Ket(ket)) => Value::Scalar(bra.try_mul(&ket).map_err(|_| mismatch(span))?),
+        (Value::Ket(ket), Value::Bra(bra)) => Value::Outer(ket.try_mul(&bra).map_err(|_| mismatch(span))?),
+        (Value::Outer(outer), Value::Ket(ket)) => Value::Ket(outer.try_mul(&ket).map_err(|_| mismatch(span))?),
+        (Value::Bra(bra), Value::Outer(outer)) => Value::Bra(bra.try_mul(&outer).map_err(|_| mismatch(span))?),
+        (Value::Outer(a), Value::Outer(b)) => Value::Outer(a.try_mul(&b).map_err(|_| mismatch(span))?),
+        // |a>|b> and <a|<b| are tensor products
+        (a @ Value::Ket(_), b @ Value::Ket(_)) | (a @ Value::Bra(_), b @ Value::Bra(_)) => tensor(a, b, span)?,
+        (Value::Ket(_), Value::Outer(_)) | (Value::Outer(_), Value::Bra(_)) => {
+            return Err(ParseError::new(ErrorKind::InvalidOperands("operator cannot act on this side"), span));
+        },
+    })
+}
+
+fn add(left: Value, right: Value, span: Span) -> Result<Value, ParseError> {
+    Ok(match (left, right) {
+        (Value::Scalar(a), Value::Scalar(b)) => Value::Scalar(a + b),
+        (Value::Ket(a), Value::Ket(b)) => Value::Ket(a.try_add(&b).map_err(|_| mismatch(span))?),
+        (Value::Bra(a), Value::Bra(b)) => Value::Bra(a.try_add(&b).map_err(|_| mismatch(span))?),
+        (Value::Outer(a), Value::Outer(b)) => Value::Outer(a.try_add(&b).map_err(|_| mismatch(span))?),
+        _ => return Err(ParseError::new(ErrorKind::InvalidOperands("only values of the same kind can be added"), span)),
+    })
+}
+
+fn negate(value: Value) -> Value {
+    match value {
+        Value::Scalar(a) => Value::Scalar(-a),
+        Value::Ket(a) => Value::Ket(-a),
+        Value::Bra(a) => Value::Bra(-a),
+        Value::Outer(a) => Value::Outer(-a),
+    }
+}
+
+fn scalar(value: Value, span: Span) -> Result<Complex, ParseError> {
+    match value {
+        Value::Scalar(a) => Ok(a),
+        _ => Err(ParseError::new(ErrorKind::InvalidOperands("scalar is expected"), span)),
+    }
+}
+
+fn tensor(left: Value, right: Value, span: Span) -> Result<Value, ParseError> {
+    Ok(match (left, right) {
+        (Value::Ket(a), Value::Ket(b)) => {
+            check_tensor(a.dim(), b.dim(), span)?;
+            Value::Ket(a.tensor(&b))
+        },
+        (Value::Bra(a), Value::Bra(b)) => {
+            check_tensor(a.dim(), b.dim(), span)?;
+            Value::Bra(a.tensor(&b))
+        },
+        (Value::Outer(a), Value::Outer(b)) => {
+            check_tensor(a.dim(), b.dim(), span)?;
+            Value::Outer(a.tensor(&b))
+        },
+        _ => return Err(ParseError::new(ErrorKind::InvalidOperands("tensor product of different kinds"), span)),
+    })
+}
+
+fn dagger(value: Value) -> Value {
+    match value {
+        Value::Scalar(a) => Value::Scalar(a.conj()),
+        Value::Ket(a) => Value::Bra(a.into()),
+        Value::Bra(a) => Value::Ket(a.into()),
+        Value::Outer(a) => Value::Outer(a.dagger()),
+    }
+}
+
+fn lookup(context: &Context, name: &str, span: Span) -> Result<Value, ParseError> {
+    context.get(name).cloned()
+        .ok_or_else(|| ParseError::new(ErrorKind::UnknownIdentifier(name.to_owned()), span))
+}
+
+fn named_ket(context: &Context, name: &str, span: Span) -> Result<DKet, ParseError> {
+    match lookup(context, name, span)? {
+        Value::Ket(ket) => Ok(ket),
+        _ => Err(ParseError::new(ErrorKind::InvalidOperands("name in brackets should refer to a ket"), span)),
+    }
+}
+
+/// Evaluate the expression with the names defined in the context
+pub(crate) fn evaluate(expr: &Expr, context: &Context) -> Result<Value, ParseError> {
+    match expr.kind {
+        ExprKind::Number(value) => Ok(Value::Scalar(value)),
+        ExprKind::Ket(ref label) => Ok(Value::Ket(label_ket(label))),
+        ExprKind::Bra(ref label) => Ok(Value::Bra(label_bra(label))),
+        ExprKind::Name(ref name) => lookup(context, name, expr.span),
+        ExprKind::NamedKet(ref name) => named_ket(context, name, expr.span).map(Value::Ket),
+        ExprKind::NamedBra(ref name) => named_ket(context, name, expr.span).map(|ket| Value::Bra(ket.into())),
+        ExprKind::Unary(op, ref operand) => {
+            let value = evaluate(operand, context)?;
+            Ok(match op {
+                UnaryOp::Neg => negate(value),
+                UnaryOp::Dagger => dagger(value),
+            })
+        },
+        ExprKind::Binary(op, ref left, ref right) => {
+            let (a, b) = (evaluate(left, context)?, evaluate(right, context)?);
+            match op {
+                BinaryOp::Add => add(a, b, expr.span),
+                BinaryOp::Sub => add(a, negate(b), expr.span),
+                BinaryOp::Mul => multiply(a, b, expr.span),
+                BinaryOp::Div => {
+                    let divisor = scalar(b, right.span)?;
+                    if divisor.norm() == 0.0 {
+                        return Err(ParseError::new(ErrorKind::InvalidOperands("division by zero"), right.span));
+                    }
+                    multiply(a, Value::Scalar(divisor.inv()), expr.span)
+                },
+                BinaryOp::Tensor => tensor(a, b, expr.span),
+            }
+        },
+        ExprKind::Call(function, ref argument) => {
+            let value = scalar(evaluate(argument, context)?, argument.span)?;
+            Ok(Value::Scalar(match function {
+                Function::Sqrt => value.sqrt(),
+                Function::Exp => value.exp(),
+            }))
+        },
+    }
+}
+
+impl Expr {
+    /// Evaluate the expression with the built-in operators of `Context::new`
+    pub fn evaluate(&self) -> Result<Value, ParseError> {
+        evaluate(self, &Context::new())
+    }
+}
+
+impl ::std::fmt::Display for Value {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            Value::Scalar(scalar) => write!(f, "{}", dirac::formatter_options(f).number(scalar, Notation::Text)),
+            Value::Ket(ref ket) => ket.fmt(f),
+            Value::Bra(ref bra) => bra.fmt(f),
+            Value::Outer(ref outer) => outer.fmt(f),
+        }
+    }
+}
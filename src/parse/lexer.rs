@@ -0,0 +1,116 @@
+use super::{ErrorKind, ParseError, Span};
+use Complex;
+
+/// Lexical token of the expression.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Token {
+    Bra(String),
+    Ket(String),
+    Number(Complex),
+    Identifier(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Tensor,
+    Dagger,
+    LeftParen,
+    RightParen,
+}
+
+// Byte length of the label of the ket sharing the bar with the preceding bra (`<a|b>`),
+// if the input continues with one
+fn shared_ket_label(rest: &str) -> Option<usize> {
+    let end = rest.find('>')?;
+    let label = &rest[..end];
+    if label.is_empty() || label.contains(|c: char| c == '|' || c == '<' || c.is_whitespace()) {
+        None
+    } else {
+        Some(end)
+    }
+}
+
+// Byte length of the prefix of `s` with all characters matching the predicate
+fn prefix_length<F: Fn(char) -> bool>(s: &str, predicate: F) -> usize {
+    s.find(|c| !predicate(c)).unwrap_or(s.len())
+}
+
+/// Split the input into tokens with their byte spans
+pub(crate) fn tokenize(input: &str) -> Result<Vec<(Token, Span)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while let Some(c) = input[position..].chars().next() {
+        let start = position;
+        let single = |token| (token, Span::new(start, start + 1));
+        match c {
+            _ if c.is_whitespace() => {
+                position += c.len_utf8();
+                continue;
+            },
+            '(' => tokens.push(single(Token::LeftParen)),
+            ')' => tokens.push(single(Token::RightParen)),
+            '+' => tokens.push(single(Token::Plus)),
+            '-' => tokens.push(single(Token::Minus)),
+            '*' => tokens.push(single(Token::Star)),
+            '/' => tokens.push(single(Token::Slash)),
+            '⊗' | '†' => {
+                let token = if c == '⊗' { Token::Tensor } else { Token::Dagger };
+                tokens.push((token, Span::new(start, start + c.len_utf8())));
+                position += c.len_utf8();
+                continue;
+            },
+            '<' => {
+                let end = input[start + 1..].find('|')
+                    .map(|offset| start + 1 + offset)
+                    .ok_or_else(|| ParseError::new(ErrorKind::UnterminatedBra, Span::new(start, input.len())))?;
+                tokens.push((Token::Bra(input[start + 1..end].to_owned()), Span::new(start, end + 1)));
+                position = end + 1;
+
+                if let Some(length) = shared_ket_label(&input[position..]) {
+                    let label = input[position..position + length].to_owned();
+                    tokens.push((Token::Ket(label), Span::new(end, position + length + 1)));
+                    position += length + 1;
+                }
+                continue;
+            },
+            '|' => {
+                let rest = &input[start + 1..];
+                let end = rest.find(['>', '|', '<'])
+                    .filter(|&offset| rest[offset..].starts_with('>'))
+                    .map(|offset| start + 1 + offset)
+                    .ok_or_else(|| ParseError::new(ErrorKind::UnterminatedKet, Span::new(start, input.len())))?;
+                tokens.push((Token::Ket(input[start + 1..end].to_owned()), Span::new(start, end + 1)));
+                position = end + 1;
+                continue;
+            },
+            _ if c.is_ascii_digit() || c == '.' => {
+                let mut end = start + prefix_length(&input[start..], |c| c.is_ascii_digit() || c == '.');
+                let value: f64 = input[start..end].parse()
+                    .map_err(|_| ParseError::new(ErrorKind::InvalidNumber, Span::new(start, end)))?;
+
+                // `0.8i` is imaginary, unless `i` starts an identifier
+                let rest = &input[end..];
+                let imaginary = rest.starts_with('i') && !rest[1..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+                if imaginary {
+                    end += 1;
+                }
+                let number = if imaginary { Complex::new(0.0, value) } else { Complex::new(value, 0.0) };
+                tokens.push((Token::Number(number), Span::new(start, end)));
+                position = end;
+                continue;
+            },
+            _ if c.is_alphabetic() => {
+                let end = start + prefix_length(&input[start..], |c| c.is_alphanumeric() || c == '_');
+                tokens.push((Token::Identifier(input[start..end].to_owned()), Span::new(start, end)));
+                position = end;
+                continue;
+            },
+            _ => return Err(ParseError::new(ErrorKind::UnexpectedCharacter(c), Span::new(start, start + c.len_utf8()))),
+        }
+        // single byte tokens
+        position += 1;
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,159 @@
+//! Dirac notation expressions, e.g. `<u|d>`, `0.6|00> + 0.8i|11>` or `(|0> - |1>)/sqrt(2)`.
+//!
+//! Expressions are parsed into an `Expr` tree and evaluated to a runtime-sized `Value`.
+//! Labels of bras and kets are sequences of qubit basis symbols, tensored left to right
+//! (`|01>` is `|0> ⊗ |1>`):
+//!
+//! - `0` or `u`: up, [1, 0]
+//! - `1` or `d`: down, [0, 1]
+//! - `+` or `r`: right, 1/√2[1, 1]
+//! - `-` or `l`: left, 1/√2[1, -1]
+//! - `i`: inward, 1/√2[1, i]
+//! - `o`: outward, 1/√2[1, -i]
+//!
+//! `0` is up as in the Dirac output (`Ket2::up()` prints as `|0⟩`) and the measurement labels.
+//! The example parser this module replaces mapped `1` to up and `0` to down, so `|1>` and `<1|`
+//! now denote the opposite state. `Ket::from_bits` still maps set bits to up.
+//!
+//! Every label is expanded into a dense state, so it is limited to `MAX_LABEL_QUBITS` symbols,
+//! and tensor products (`⊗` or juxtaposed kets and bras) are limited to `MAX_QUBITS` qubits.
+//!
+//! Scalars are real literals (`0.5`), imaginary literals (`2i`, `i`), `pi`, and `sqrt(a)` or
+//! `exp(a)` of scalars. Other names refer to values of the `Context`, e.g. operators `H` or
+//! `CNOT`; a name in brackets refers to a ket, `|ψ>`, or its bra, `<ψ|`, so expectation values
+//! are written as `<ψ|A|ψ>`. Labels made only of basis symbols (e.g. `|lol>`) are not names.
+//!
+//! Operators by precedence: postfix dagger `†`; unary `-`; tensor product `⊗`; `*`, `/` (by
+//! a scalar) and juxtaposition; `+` and `-`, all binary ones left associative. Juxtaposed
+//! items are multiplied (`<a|b>` is an inner product, `|a><b|` an outer product, `|a>|b>`
+//! a tensor product), e.g. `<0|H|1>` or `CNOT (H⊗I) |00>`. A bra-ket sharing the bar, `<a|b>`,
+//! is a single bracket, and the tensor product is defined only for values of the same kind.
+
+mod ast;
+mod context;
+mod eval;
+mod lexer;
+
+pub use self::ast::{BinaryOp, Expr, ExprKind, Function, UnaryOp};
+pub use self::context::Context;
+pub use self::eval::Value;
+
+/// Maximal number of qubit symbols in a single label.
+pub const MAX_LABEL_QUBITS: usize = 10;
+
+/// Maximal number of qubits of a tensor product.
+pub const MAX_QUBITS: usize = 10;
+
+/// Names of the scalars and functions, e.g. `pi` or `sqrt`, which shadow names of the `Context`.
+pub const KEYWORDS: [&str; 4] = ["i", "pi", "sqrt", "exp"];
+
+// Symbols of the qubit basis allowed in labels
+const LABEL_SYMBOLS: &str = "01udrlio+-";
+
+/// Whether the text is made only of qubit basis symbols, so `|text>` is a basis state even
+/// if the text is also a name of the `Context`, e.g. `|lol>`
+pub fn is_label(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| LABEL_SYMBOLS.contains(c))
+}
+
+/// Byte range of the input, `start..end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// Offset of the first byte
+    pub start: usize,
+    /// Offset after the last byte
+    pub end: usize,
+}
+
+impl Span {
+    pub(crate) fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    // Smallest span covering both
+    pub(crate) fn join(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+/// Kind of the parsing or evaluation error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Character which cannot start any token
+    UnexpectedCharacter(char),
+    /// Bra without the closing `|`
+    UnterminatedBra,
+    /// Ket without the closing `>`
+    UnterminatedKet,
+    /// Bra or ket without a label, `<|` or `|>`
+    EmptyLabel,
+    /// Label symbol outside of the qubit basis
+    UnknownLabel(char),
+    /// Label of more than `MAX_LABEL_QUBITS` symbols
+    LabelTooLong,
+    /// Malformed number literal, e.g. `1.2.3`
+    InvalidNumber,
+    /// Name which is not defined in the context
+    UnknownIdentifier(String),
+    /// Token which does not fit the grammar
+    UnexpectedToken,
+    /// Input ended in the middle of an expression
+    UnexpectedEnd,
+    /// `(` without the matching `)`
+    UnclosedParenthesis,
+    /// Operation is not defined for the operands, e.g. dimensions do not match
+    InvalidOperands(&'static str),
+}
+
+/// Error of parsing or evaluating an expression, with the byte span of the offending input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// What went wrong
+    pub kind: ErrorKind,
+    /// Where it went wrong
+    pub span: Span,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ErrorKind, span: Span) -> ParseError {
+        ParseError { kind, span }
+    }
+}
+
+impl ::std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character '{}'", c),
+            ErrorKind::UnterminatedBra => write!(f, "bra is not closed with '|'"),
+            ErrorKind::UnterminatedKet => write!(f, "ket is not closed with '>'"),
+            ErrorKind::EmptyLabel => write!(f, "empty label"),
+            ErrorKind::UnknownLabel(c) => write!(f, "unknown label '{}'", c),
+            ErrorKind::LabelTooLong => write!(f, "label is longer than {} qubits", MAX_LABEL_QUBITS),
+            ErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ErrorKind::UnknownIdentifier(ref name) => write!(f, "unknown identifier '{}'", name),
+            ErrorKind::UnexpectedToken => write!(f, "unexpected token"),
+            ErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ErrorKind::UnclosedParenthesis => write!(f, "parenthesis is not closed"),
+            ErrorKind::InvalidOperands(reason) => write!(f, "invalid operands: {}", reason),
+        }
+    }
+}
+
+impl ::std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "{} at {}..{}", self.kind, self.span.start, self.span.end)
+    }
+}
+
+impl ::std::error::Error for ParseError {}
+
+/// Parse the expression into the syntax tree
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let tokens = lexer::tokenize(input)?;
+    ast::Parser::new(&tokens, input.len()).parse()
+}
+
+/// Parse and evaluate the expression with the built-in operators of `Context::new`
+pub fn evaluate(input: &str) -> Result<Value, ParseError> {
+    Context::new().evaluate_str(input)
+}
@@ -1,6 +1,21 @@
+use std::convert::TryFrom;
+
 use assert_approx_eq::assert_approx_eq;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use nalgebra::{DMatrix, U2, U3, U4, Vector3, Vector4, Matrix2, Matrix4};
+
+use {parse, Basis, Channel, Circuit, DensityMatrix, DiracFormat, Labels, LatexStyle, Lindblad, Ket, Bra, Outer, DKet, DBra, DOuter, Bra2, Bra4, Ket2, Ket4, Outer2, Outer4, SQRT_2_INVERSE, Complex, Trajectories, Trotter, TryAdd, TryMul, U8};
 
-use {Ket, Bra2, Bra4, Ket2, Ket4, Outer2, Outer4, SQRT_2_INVERSE, Complex, U8};
+fn assert_close<'a, A, B>(left: A, right: B)
+    where A: IntoIterator<Item=&'a Complex>, B: IntoIterator<Item=&'a Complex>
+{
+    for (l, r) in left.into_iter().zip(right) {
+        assert_approx_eq!(l.re, r.re);
+        assert_approx_eq!(l.im, r.im);
+    }
+}
 
 #[test]
 fn orthogonal() {
@@ -146,4 +161,1299 @@ fn bits() {
     for i in 1..8 {
         assert_approx_eq!(q111_bits.0.get(i).expect("at least 8").re, 0.0);
     }
-}
\ No newline at end of file
+}
+#[test]
+fn tensor_qubit_qutrit() {
+    let qutrit: Ket<U3> = Vector3::new(0.0.into(), 1.0.into(), 0.0.into()).into();
+
+    let state = Ket2::down().tensor(qutrit);
+
+    // |down> ⊗ |1> sits at index 1 * 3 + 1
+    for i in 0..6 {
+        assert_approx_eq!(state.0[i].re, if i == 4 { 1.0 } else { 0.0 });
+    }
+}
+
+#[test]
+fn tensor_three_qubits() {
+    let register: Ket<U8> = Ket2::up().tensor(Ket2::up()).tensor(Ket2::up());
+    let hii = Outer2::h2().tensor(Outer2::identity()).tensor(Outer2::identity());
+
+    let expected = (Outer2::h2() * Ket2::up()).tensor(Ket2::up().cross(Ket2::up()));
+
+    assert_eq!(hii * register, expected);
+
+    let bra: Bra<U8> = Bra2::down().tensor(Bra4::from(Ket2::up().cross(Ket2::up())));
+    assert_approx_eq!((bra * expected).re, SQRT_2_INVERSE);
+}
+
+#[test]
+fn bra_conjugate_storage() {
+    // <i|A|1> with <i| = (<0| - i<1|)/√2 and A|1> = 2i|0> + 4|1>, i.e. (2i - 4i)/√2
+    let a = Outer2::from(Matrix2::new(1.0.into(), Complex::new(0.0, 2.0), 3.0.into(), 4.0.into()));
+    let value = (Bra2::inw() * &a) * Ket2::down();
+    assert_approx_eq!(value.re, 0.0);
+    assert_approx_eq!(value.im, -(2.0f64.sqrt()));
+
+    // 2i<i|1> = 2i * (-i/√2) = √2
+    let value = (Bra2::inw() * Complex::new(0.0, 2.0)) * Ket2::down();
+    assert_approx_eq!(value.re, 2.0f64.sqrt());
+    assert_approx_eq!(value.im, 0.0);
+}
+
+#[test]
+fn operator_algebra() {
+    let h = Outer2::h2();
+    let z = Outer2::z2();
+    let x = Outer2::n2();
+
+    // HZH = X
+    assert_close((&h * &z * &h).0.iter(), x.0.iter());
+
+    // [X, Z] = XZ - ZX = -2iY
+    let commutator = &x * &z - &z * &x;
+    let y = Outer2::from(Matrix2::new(0.0.into(), -Complex::i(), Complex::i(), 0.0.into()));
+    assert_eq!(commutator, &y * Complex::new(0.0, -2.0));
+
+    // global phases and scalar division cancel out
+    let phase = Complex::new(0.0, 1.0);
+    assert_eq!((&h * phase) / phase, h);
+    assert_eq!(-(-&h), h);
+    assert_eq!(&h - &h, Outer2::identity() * 0.0);
+    assert_close((h.clone() * 2.0 / 2.0).0.iter(), h.0.iter());
+}
+
+#[test]
+fn state_algebra() {
+    let up = Ket2::up();
+    let down = Ket2::down();
+
+    assert_close(((&up - &down) * Complex::from(SQRT_2_INVERSE)).0.iter(), Ket2::left().0.iter());
+    assert_close(((&up + &down) / Complex::from(2.0f64.sqrt())).0.iter(), Ket2::right().0.iter());
+    assert_eq!(-(-&up), up);
+
+    // the bra is scaled itself: <i| * i = i<i| so <i|i> * i
+    let bra = Bra2::inw() * Complex::i();
+    assert_approx_eq!((&bra * &Ket2::inw()).im, 1.0);
+    assert_approx_eq!(((&bra / Complex::i()) * &Ket2::inw()).re, 1.0);
+    assert_eq!(Bra2::up() - Bra2::up(), Bra2::down() * Complex::from(0.0));
+}
+
+#[test]
+fn bra_operator_associativity() {
+    let y = Outer2::from(Matrix2::new(0.0.into(), -Complex::i(), Complex::i(), 0.0.into()));
+
+    // (<i|Y)|u> == <i|(Y|u>)
+    let left = (&Bra2::inw() * &y) * &Ket2::up();
+    let right = &Bra2::inw() * &(&y * &Ket2::up());
+
+    assert_approx_eq!(left.re, right.re);
+    assert_approx_eq!(left.im, right.im);
+    // Y|u> = i|d>, <i|d> = -i/√2
+    assert_approx_eq!(right.re, SQRT_2_INVERSE);
+}
+
+#[test]
+fn dagger_trace_determinant() {
+    let y = Outer2::from(Matrix2::new(0.0.into(), -Complex::i(), Complex::i(), 0.0.into()));
+    let projector = Ket2::inw() * Bra2::inw();
+
+    // Y and projectors are hermitian
+    assert_eq!(y.dagger(), y);
+    assert_close(projector.dagger().0.iter(), projector.0.iter());
+
+    // U†U = I
+    let u = &Outer2::h2() * &(Ket2::up() * Bra2::up() + Ket2::down() * Bra2::down() * Complex::i());
+    assert_close((u.dagger() * &u).0.iter(), Outer2::identity().0.iter());
+
+    assert_approx_eq!(projector.trace().re, 1.0);
+    assert_approx_eq!(Outer4::identity().trace().re, 4.0);
+
+    assert_approx_eq!(Outer2::h2().determinant().re, -1.0);
+    assert_approx_eq!(Outer4::cnot().determinant().re, -1.0);
+    assert_approx_eq!(y.determinant().re, -1.0);
+}
+
+#[test]
+fn operator_functions() {
+    let x = Outer2::n2();
+    let z = Outer2::z2();
+
+    assert_eq!(Outer2::h2().pow(0), Outer2::identity());
+    assert_close(Outer2::h2().pow(2).0.iter(), Outer2::identity().0.iter());
+    assert_close(Outer4::qft().pow(4).0.iter(), Outer4::identity().0.iter());
+
+    // e^(-iπX/2) = -iX
+    let rotation = (&x * Complex::new(0.0, -::std::f64::consts::FRAC_PI_2)).exp();
+    assert_close(rotation.0.iter(), (&x * -Complex::i()).0.iter());
+
+    // √Z = S, √X squared is X
+    let s = Ket2::up() * Bra2::up() + Ket2::down() * Bra2::down() * Complex::i();
+    assert_close(z.sqrt().0.iter(), s.0.iter());
+    assert_close(x.sqrt().pow(2).0.iter(), x.0.iter());
+
+    // log(e^(iθZ)) = iθZ
+    let generator = &z * Complex::new(0.0, 0.3);
+    assert_close(generator.exp().log().0.iter(), generator.0.iter());
+
+    // e^(iπ/4 QFT) works for non-hermitian normal operators
+    let qft = Outer4::qft();
+    assert_close((&qft * Complex::new(0.0, 0.25)).exp().dagger().0.iter(), (qft.dagger() * Complex::new(0.0, -0.25)).exp().0.iter());
+}
+
+fn matrix4(rows: [[Complex; 4]; 4]) -> Outer4 {
+    Outer4::from(Matrix4::from_fn(|i, j| rows[i][j]))
+}
+
+#[test]
+fn single_qubit_gates() {
+    use std::f64::consts::{PI, FRAC_PI_2, FRAC_PI_4};
+
+    let i = Complex::i();
+    let x = Outer2::n2();
+    let z = Outer2::z2();
+    let y = Outer2::y2();
+
+    assert_eq!(y.clone().into_matrix(), Matrix2::new(0.0.into(), -i, i, 0.0.into()));
+    // Y = iXZ
+    assert_close(y.0.iter(), (&x * &z * i).0.iter());
+
+    let s = Outer2::s2();
+    let t = Outer2::t2();
+    assert_eq!(s.clone().into_matrix(), Matrix2::new(1.0.into(), 0.0.into(), 0.0.into(), i));
+    assert_close(t.0.iter(), Matrix2::new(1.0.into(), 0.0.into(), 0.0.into(), Complex::new(SQRT_2_INVERSE, SQRT_2_INVERSE)).iter());
+    assert_close(s.pow(2).0.iter(), z.0.iter());
+    assert_close(t.pow(2).0.iter(), s.0.iter());
+    assert_close(Outer2::sdg2().0.iter(), s.dagger().0.iter());
+    assert_close(Outer2::tdg2().0.iter(), t.dagger().0.iter());
+    assert_close(Outer2::sx2().pow(2).0.iter(), x.0.iter());
+    assert_close(Outer2::p2(PI).0.iter(), z.0.iter());
+
+    assert_close(Outer2::rx2(PI).0.iter(), (&x * -i).0.iter());
+    assert_close(Outer2::ry2(PI).0.iter(), (&y * -i).0.iter());
+    assert_close(Outer2::rz2(PI).0.iter(), (&z * -i).0.iter());
+    // Rz differs from phase gate only by global phase
+    assert_close(Outer2::rz2(FRAC_PI_4).0.iter(), (Outer2::p2(FRAC_PI_4) * Complex::from_polar(&1.0, &(-PI / 8.0))).0.iter());
+
+    // U3(π/2, 0, π) = H, U3(θ, -π/2, π/2) = Rx(θ)
+    assert_close(Outer2::u3(FRAC_PI_2, 0.0, PI).0.iter(), Outer2::h2().0.iter());
+    assert_close(Outer2::u3(0.3, -FRAC_PI_2, FRAC_PI_2).0.iter(), Outer2::rx2(0.3).0.iter());
+}
+
+#[test]
+fn two_qubit_gates() {
+    let (o, l, i) = (Complex::from(0.0), Complex::from(1.0), Complex::i());
+    let ih = Outer2::identity().tensor(Outer2::h2());
+    let cnot = Outer4::cnot();
+
+    assert_eq!(Outer4::cz(), matrix4([[l, o, o, o], [o, l, o, o], [o, o, l, o], [o, o, o, -l]]));
+    assert_close(Outer4::cz().0.iter(), (&ih * &cnot * &ih).0.iter());
+    assert_eq!(Outer4::cy(), matrix4([[l, o, o, o], [o, l, o, o], [o, o, o, -i], [o, o, i, o]]));
+    assert_close(Outer4::cphase(::std::f64::consts::PI).0.iter(), Outer4::cz().0.iter());
+    assert_close(Outer4::cphase(1.0).0.iter(), matrix4([[l, o, o, o], [o, l, o, o], [o, o, l, o], [o, o, o, Complex::from_polar(&1.0, &1.0)]]).0.iter());
+
+    // SWAP = three CNOTs with alternating control
+    let reversed = &Outer2::h2().tensor(Outer2::h2()) * &cnot * &Outer2::h2().tensor(Outer2::h2());
+    assert_eq!(Outer4::swap(), matrix4([[l, o, o, o], [o, o, l, o], [o, l, o, o], [o, o, o, l]]));
+    assert_close((&cnot * &reversed * &cnot).0.iter(), Outer4::swap().0.iter());
+
+    assert_eq!(Outer4::iswap(), matrix4([[l, o, o, o], [o, o, i, o], [o, i, o, o], [o, o, o, l]]));
+    assert_close(Outer4::sqrt_swap().pow(2).0.iter(), Outer4::swap().0.iter());
+}
+
+#[test]
+fn three_qubit_gates() {
+    let toffoli = Outer::<U8>::toffoli();
+    let fredkin = Outer::<U8>::fredkin();
+
+    let d = Ket2::down;
+    let u = Ket2::up;
+
+    // |110> -> |111>, |010> unchanged
+    assert_eq!(&toffoli * d().tensor(d()).tensor(u()), d().tensor(d()).tensor(d()));
+    assert_eq!(&toffoli * u().tensor(d()).tensor(u()), u().tensor(d()).tensor(u()));
+
+    // |101> -> |110>, |001> unchanged
+    assert_eq!(&fredkin * d().tensor(u()).tensor(d()), d().tensor(d()).tensor(u()));
+    assert_eq!(&fredkin * u().tensor(u()).tensor(d()), u().tensor(u()).tensor(d()));
+
+    assert_eq!(toffoli.pow(2), Outer::<U8>::identity());
+    assert_eq!(fredkin.dagger(), fredkin);
+}
+
+#[test]
+fn embed_gates() {
+    let h = Outer2::h2();
+    let i = Outer2::identity;
+
+    let ihi: Outer<U8> = h.on(1).expect("qubit 1 is in the 3 qubit register");
+    assert_eq!(ihi, i().tensor(h.clone()).tensor(i()));
+
+    // CNOT with control and target swapped
+    let reversed: Outer4 = Outer4::cnot().on_pair(1, 0).expect("qubits 0, 1 are in the 2 qubit register");
+    let hh = Outer2::h2().tensor(Outer2::h2());
+    assert_close(reversed.0.iter(), (&hh * Outer4::cnot() * &hh).0.iter());
+
+    // CNOT on qubits 0, 2 flips the last qubit of |1x0>
+    let cnot02: Outer<U8> = Outer4::cnot().on_pair(0, 2).expect("qubits 0, 2 are in the 3 qubit register");
+    let d = Ket2::down;
+    let u = Ket2::up;
+    assert_eq!(&cnot02 * d().tensor(u()).tensor(u()), d().tensor(u()).tensor(d()));
+    assert_eq!(&cnot02 * u().tensor(d()).tensor(u()), u().tensor(d()).tensor(u()));
+
+    assert!(h.on::<U8>(3).is_err());
+    assert!(Outer4::cnot().on_pair::<U8>(1, 1).is_err());
+    assert!(h.on::<U3>(0).is_err());
+}
+
+#[test]
+fn apply_gate_in_place() {
+    let mut state: Ket<U8> = Ket2::up().tensor(Ket2::up()).tensor(Ket2::up());
+
+    state.apply_gate(&Outer2::h2(), &[0]).expect("qubit 0 is in the register");
+    state.apply_gate(&Outer4::cnot(), &[0, 1]).expect("qubits 0, 1 are in the register");
+    state.apply_gate(&Outer4::cnot(), &[1, 2]).expect("qubits 1, 2 are in the register");
+
+    // GHZ state (|000> + |111>)/√2
+    let ghz = (Ket2::up().tensor(Ket2::up()).tensor(Ket2::up())
+        + Ket2::down().tensor(Ket2::down()).tensor(Ket2::down())) * Complex::from(SQRT_2_INVERSE);
+    assert_close(state.0.iter(), ghz.0.iter());
+
+    // matrix-free application agrees with the embedded operator
+    let rotation = Outer2::u3(0.3, 0.2, 0.1);
+    let embedded: Outer<U8> = rotation.on(2).expect("qubit 2 is in the register");
+    let expected = &embedded * &state;
+    state.apply_gate(&rotation, &[2]).expect("qubit 2 is in the register");
+    assert_close(state.0.iter(), expected.0.iter());
+
+    assert!(state.apply_gate(&Outer4::cnot(), &[0]).is_err());
+    assert!(state.apply_gate(&Outer2::h2(), &[5]).is_err());
+    // more qubits than in the register, 2^64 would overflow
+    assert!(state.apply_gate(&Outer2::h2(), &[0; 64]).is_err());
+}
+
+#[test]
+fn controlled_gates() {
+    assert_eq!(Outer2::n2().controlled(), Outer4::cnot());
+    assert_eq!(Outer2::z2().controlled(), Outer4::cz());
+    assert_eq!(Outer2::y2().controlled(), Outer4::cy());
+    assert_eq!(Outer4::swap().controlled(), Outer::<U8>::fredkin());
+    assert_eq!(Outer2::n2().controlled_by::<U8>(2), Ok(Outer::<U8>::toffoli()));
+    assert_eq!(Outer2::n2().controlled().controlled(), Outer::<U8>::toffoli());
+
+    // negative control applies when the control qubit is |0>
+    let x_on_control = Outer2::n2().tensor(Outer2::identity());
+    let negative: Outer4 = Outer2::n2().controlled_on(&[false]).expect("U4 fits one control");
+    assert_eq!(negative, &x_on_control * Outer4::cnot() * &x_on_control);
+
+    // controlled-H acts only on |1x>
+    let ch = Outer2::h2().controlled();
+    assert_eq!(&ch * Ket2::up().cross(Ket2::down()), Ket2::up().cross(Ket2::down()));
+    assert_eq!(&ch * Ket2::down().cross(Ket2::up()), Ket2::down().cross(Outer2::h2() * Ket2::up()));
+
+    assert!(Outer2::n2().controlled_by::<U8>(1).is_err());
+    assert!(Outer2::n2().controlled_on::<U8>(&[true, false, true]).is_err());
+}
+
+#[test]
+fn dynamic_bell_state() {
+    let up = DKet::from(Ket2::up());
+    let down = DKet::from(Ket2::down());
+
+    let bell = (up.tensor(&up) + down.tensor(&down)) * Complex::from(SQRT_2_INVERSE);
+    let cnot = DOuter::from(Outer4::cnot());
+    let h = DOuter::from(Outer2::h2()).tensor(&DOuter::identity(2));
+
+    assert_close((&cnot * &h * up.tensor(&up)).0.iter(), bell.0.iter());
+    assert_approx_eq!((DBra::from(up.tensor(&up)) * &bell).norm().powi(2), 0.5);
+    assert_approx_eq!((&bell * DBra::from(bell.clone())).trace().re, 1.0);
+    assert_approx_eq!(cnot.determinant().re, -1.0);
+    assert_close(h.pow(2).0.iter(), DOuter::identity(4).0.iter());
+}
+
+#[test]
+fn dynamic_conversions() {
+    let ket = Ket2::inw();
+    let round_trip = Ket2::try_from(DKet::from(ket.clone()));
+    assert_eq!(round_trip, Ok(ket));
+
+    let outer = Outer4::iswap();
+    assert_eq!(Outer4::try_from(DOuter::from(outer.clone())), Ok(outer));
+
+    let bra = Bra2::out();
+    assert_eq!(Bra2::try_from(DBra::from(bra.clone())), Ok(bra));
+
+    assert!(Ket4::try_from(DKet::from(Ket2::up())).is_err());
+    assert!(Outer2::try_from(DOuter::identity(4)).is_err());
+
+    assert_eq!(DOuter::try_from(DMatrix::identity(3, 3)), Ok(DOuter::identity(3)));
+    assert_eq!(DOuter::try_from(DMatrix::zeros(2, 3)), Err("Operator matrix should be square"));
+}
+
+#[test]
+fn dynamic_checked_arithmetic() {
+    let up = DKet::from(Ket2::up());
+    let up2 = up.tensor(&up);
+    let h = DOuter::from(Outer2::h2());
+
+    assert_eq!(up.try_add(&up), Ok(&up + &up));
+    assert_eq!(h.try_sub(&h), Ok(&h - &h));
+    assert_eq!(h.try_mul(&up), Ok(&h * &up));
+    assert_eq!(DBra::from(up.clone()).try_mul(&up), Ok(Complex::from(1.0)));
+
+    assert_eq!(up.try_add(&up2), Err("Dimensions do not match"));
+    assert_eq!(DBra::from(up2.clone()).try_sub(&DBra::from(up.clone())), Err("Dimensions do not match"));
+    assert_eq!(up.try_mul(&DBra::from(up2.clone())), Err("Dimensions do not match"));
+    assert_eq!(DBra::from(up2.clone()).try_mul(&h), Err("Dimensions do not match"));
+    assert_eq!(h.try_mul(&up2), Err("Dimensions do not match"));
+    assert_eq!(h.try_mul(&DOuter::identity(4)), Err("Dimensions do not match"));
+}
+
+#[test]
+fn dynamic_large_register() {
+    // 10 qubits is beyond static aliases
+    let n = 10;
+    let mut state = DKet::basis(1 << n, 0).expect("0 is a valid basis index");
+    let h = DOuter::from(Outer2::h2());
+    let cnot = DOuter::from(Outer4::cnot());
+
+    state.apply_gate(&h, &[0]).expect("qubit 0 is in the register");
+    for q in 0..n - 1 {
+        state.apply_gate(&cnot, &[q, q + 1]).expect("adjacent qubits are in the register");
+    }
+
+    assert_approx_eq!(state.0[0].re, SQRT_2_INVERSE);
+    assert_approx_eq!(state.0[(1 << n) - 1].re, SQRT_2_INVERSE);
+    assert_approx_eq!(state.0.norm(), 1.0);
+
+    let x9 = DOuter::from(Outer2::n2()).on_qubits(&[9], 1 << n).expect("qubit 9 is in the register");
+    assert_approx_eq!((&x9 * &state).0[1].re, SQRT_2_INVERSE);
+    assert!(DKet::basis(4, 4).is_err());
+}
+
+#[test]
+fn circuit_bell_state() {
+    let mut circuit = Circuit::new(2);
+    circuit.h(0).cnot(0, 1);
+
+    let bell_state = (Ket2::up().cross(Ket2::up()) + Ket2::down().cross(Ket2::down())) * Complex::from(SQRT_2_INVERSE);
+    let result = circuit.run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+
+    assert_close(result.0.iter(), bell_state.0.iter());
+
+    let unitary: Outer4 = circuit.unitary().expect("4 dim operator fits 2 qubits");
+    assert_close(unitary.0.iter(), (Outer4::cnot() * Outer2::h2().tensor(Outer2::identity())).0.iter());
+
+    assert!(circuit.run(&Ket::<U8>::from_bits(0).expect("0 fits 3 qubits")).is_err());
+    assert!(circuit.unitary::<U8>().is_err());
+}
+
+#[test]
+fn circuit_structure() {
+    let mut circuit = Circuit::new(3);
+    circuit.h(0).h(1).h(2).cnot(0, 1).rz(2, 0.4).toffoli(0, 1, 2).t(1);
+
+    assert_eq!(circuit.gate_count(), 7);
+    // H layer, CNOT + Rz layer, Toffoli, T
+    assert_eq!(circuit.depth(), 4);
+    assert_eq!(Circuit::new(2).depth(), 0);
+
+    let inverse = circuit.inverse();
+    assert_eq!(inverse.instructions()[0].name(), "T†");
+    assert_eq!(inverse.instructions()[0].qubits(), &[1]);
+
+    let state = Ket2::up().tensor(Ket2::down()).tensor(Ket2::up());
+    let evolved = circuit.run(&state).expect("8 dim state fits 3 qubits");
+    assert_close(inverse.run(&evolved).expect("8 dim state fits 3 qubits").0.iter(), state.0.iter());
+
+    let composed: Outer<U8> = circuit.unitary().expect("8 dim operator fits 3 qubits");
+    let inverse_composed: Outer<U8> = inverse.unitary().expect("8 dim operator fits 3 qubits");
+    assert_close((composed * inverse_composed).0.iter(), Outer::<U8>::identity().0.iter());
+}
+
+#[test]
+fn circuit_arbitrary_gates() {
+    let mut circuit = Circuit::new(2);
+
+    assert!(circuit.gate("CH", Outer2::h2().controlled(), &[1, 0]).is_ok());
+    assert!(circuit.gate("H", Outer2::h2(), &[0, 1]).is_err());
+    assert!(circuit.gate("H", Outer2::h2(), &[2]).is_err());
+    assert!(circuit.gate("CNOT", Outer4::cnot(), &[1, 1]).is_err());
+    assert!(circuit.gate("I", DOuter::identity(2), &[0; 64]).is_err());
+    assert_eq!(circuit.gate_count(), 1);
+    let ch: Outer4 = Outer2::h2().controlled().on_qubits(&[1, 0]).expect("qubits 0, 1 are in the register");
+    assert_eq!(circuit.unitary_dynamic(), Ok(DOuter::from(ch)));
+
+    // 2^64 amplitudes cannot be indexed
+    let huge = Circuit::new(64);
+    assert!(huge.unitary_dynamic().is_err());
+    assert!(huge.run_dynamic(&DKet::basis(2, 0).expect("0 < 2")).is_err());
+
+    let state = DKet::from(Ket2::up().cross(Ket2::down()));
+    let result = circuit.run_dynamic(&state).expect("4 dim state fits 2 qubits");
+    assert_close(result.0.iter(), DKet::from((Outer2::h2() * Ket2::up()).cross(Ket2::down())).0.iter());
+}
+
+#[test]
+#[should_panic]
+fn circuit_invalid_qubit() {
+    Circuit::new(2).cnot(0, 2);
+}
+
+#[test]
+fn measurement_probabilities() {
+    let probabilities = Ket2::inw().probabilities();
+    assert_approx_eq!(probabilities[0], 0.5);
+    assert_approx_eq!(probabilities[1], 0.5);
+
+    let unnormalized = Ket2::up() * Complex::from(2.0);
+    assert_eq!(unnormalized.probabilities(), vec![1.0, 0.0]);
+}
+
+#[test]
+fn measurement_collapse() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut circuit = Circuit::new(2);
+    circuit.h(0).cnot(0, 1);
+    let bell = circuit.run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+
+    for _ in 0..20 {
+        let (outcome, state) = bell.measure(&mut rng);
+        assert!(outcome == 0 || outcome == 3);
+        assert_approx_eq!(state.0[outcome].norm(), 1.0);
+        assert_approx_eq!(state.0.norm(), 1.0);
+
+        // measuring one qubit of the bell pair fixes the other one
+        let (first, collapsed) = bell.measure_qubit(0, &mut rng).expect("qubit 0 is in the register");
+        let (second, _) = collapsed.measure_qubit(1, &mut rng).expect("qubit 1 is in the register");
+        assert_eq!(first, second);
+        assert_approx_eq!(collapsed.0.norm(), 1.0);
+    }
+
+    assert!(bell.measure_qubit(2, &mut rng).is_err());
+}
+
+#[test]
+fn measurement_sampling() {
+    let mut circuit = Circuit::new(3);
+    circuit.h(0).x(2);
+    let state = circuit.run(&Ket2::up().tensor(Ket2::up()).tensor(Ket2::up())).expect("8 dim state fits 3 qubits");
+
+    let histogram = state.sample(1000, &mut StdRng::seed_from_u64(7));
+    assert_eq!(histogram.keys().collect::<Vec<_>>(), vec!["001", "101"]);
+    assert_eq!(histogram.values().sum::<usize>(), 1000);
+    assert!((histogram["001"] as i64 - 500).abs() < 60);
+
+    // same seed gives same histogram
+    assert_eq!(histogram, state.sample(1000, &mut StdRng::seed_from_u64(7)));
+
+    let qutrit: Ket<U3> = Vector3::new(0.0.into(), 1.0.into(), 0.0.into()).into();
+    assert_eq!(qutrit.sample(10, &mut StdRng::seed_from_u64(7))["1"], 10);
+
+    // set bits of from_bits are up, labeled 0
+    let bits: Ket<U8> = Ket::from_bits(0b110).expect("110 are valid ket8 bits");
+    assert_eq!(bits.sample(10, &mut StdRng::seed_from_u64(7))["001"], 10);
+
+    // runtime dimension kets sample the same way
+    let dynamic = DKet::from(state.clone());
+    assert_eq!(dynamic.probabilities(), state.probabilities());
+    assert_eq!(dynamic.sample(1000, &mut StdRng::seed_from_u64(7)), histogram);
+}
+
+#[test]
+fn basis_validation() {
+    assert_eq!(Basis::new(vec![Ket2::right(), Ket2::left()]), Ok(Basis::x()));
+    assert_eq!(Basis::new(vec![Ket2::inw(), Ket2::out()]), Ok(Basis::y()));
+    assert_eq!(Basis::<U2>::computational(), Basis::z());
+
+    assert!(Basis::new(vec![Ket2::up()]).is_err());
+    assert!(Basis::new(vec![Ket2::up(), Ket2::right()]).is_err());
+    assert!(Basis::new(vec![Ket2::up() * Complex::from(2.0), Ket2::down()]).is_err());
+}
+
+#[test]
+fn basis_change() {
+    // hadamard takes Z basis to X basis
+    assert_close(Basis::z().change_to(&Basis::x()).0.iter(), Outer2::h2().0.iter());
+    assert_close(Basis::x().change_to(&Basis::z()).0.iter(), Outer2::h2().0.iter());
+    assert_close(Basis::z().change_to(&Basis::y()).0.iter(), (Outer2::s2() * Outer2::h2()).0.iter());
+
+    let coordinates = Basis::x().coordinates(&Ket2::up());
+    assert_close(coordinates.0.iter(), Ket2::right().0.iter());
+}
+
+#[test]
+fn stern_gerlach() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let probabilities = Ket2::up().probabilities_in(&Basis::x());
+    assert_approx_eq!(probabilities[0], 0.5);
+    assert_approx_eq!(probabilities[1], 0.5);
+    assert_approx_eq!(Ket2::up().probabilities_in(&Basis::z())[0], 1.0);
+
+    // up -> left-right analyzer -> up-down analyzer gives up only a half of the times
+    let mut ups = 0;
+    for _ in 0..1000 {
+        let (_, analyzed) = Ket2::up().measure_in(&Basis::x(), &mut rng);
+        let probabilities = analyzed.probabilities_in(&Basis::x());
+        assert!(probabilities[0] < 1e-9 || probabilities[0] > 1.0 - 1e-9);
+
+        let (outcome, _) = analyzed.measure_in(&Basis::z(), &mut rng);
+        if outcome == 0 {
+            ups += 1;
+        }
+    }
+    assert!((ups as i64 - 500).abs() < 60);
+}
+
+#[test]
+fn density_matrix_validation() {
+    assert!(DensityMatrix::new(Ket2::inw() * Bra2::inw()).is_ok());
+    assert_eq!(DensityMatrix::try_from(Ket2::up() * Bra2::down()), Err("Density matrix should be hermitian"));
+    assert_eq!(DensityMatrix::new(Outer2::identity()), Err("Density matrix should be of unit trace"));
+    assert_eq!(
+        DensityMatrix::new(Ket2::up() * Bra2::up() * 1.5 - Ket2::down() * Bra2::down() * 0.5),
+        Err("Density matrix should be positive semi-definite")
+    );
+
+    assert!(DensityMatrix::mixture(&[(0.5, Ket2::up()), (0.6, Ket2::down())]).is_err());
+    assert!(DensityMatrix::mixture(&[(1.5, Ket2::up()), (-0.5, Ket2::down())]).is_err());
+}
+
+#[test]
+fn density_matrix_purity() {
+    let pure = DensityMatrix::from(Ket2::right() * Complex::from(3.0));
+    assert_approx_eq!(pure.purity(), 1.0);
+    assert!(pure.is_pure());
+
+    let mixed = DensityMatrix::mixture(&[(0.5, Ket2::up()), (0.5, Ket2::down())]).expect("weights sum up to 1");
+    assert_approx_eq!(mixed.purity(), 0.5);
+    assert!(!mixed.is_pure());
+    assert_eq!(mixed, DensityMatrix::maximally_mixed());
+
+    // mixture of right and left is the same as mixture of up and down
+    let mixed_x = DensityMatrix::mixture(&[(0.5, Ket2::right()), (0.5, Ket2::left())]).expect("weights sum up to 1");
+    assert_close(mixed_x.0.iter(), mixed.0.iter());
+}
+
+#[test]
+fn density_matrix_expectation_and_evolution() {
+    let rho = DensityMatrix::from(Ket2::up());
+
+    assert_approx_eq!(rho.expectation(&Outer2::z2()).re, 1.0);
+    assert_approx_eq!(rho.expectation(&Outer2::n2()).re, 0.0);
+
+    let evolved = rho.evolve(&Outer2::h2());
+    assert_approx_eq!(evolved.expectation(&Outer2::n2()).re, 1.0);
+    assert_approx_eq!(evolved.expectation(&Outer2::z2()).re, 0.0);
+
+    let mixture = DensityMatrix::mixture(&[(0.75, Ket2::up()), (0.25, Ket2::inw())]).expect("weights sum up to 1");
+    assert_approx_eq!(mixture.expectation(&Outer2::y2()).re, 0.25);
+    assert_approx_eq!(mixture.evolve(&Outer2::rx2(0.7)).purity(), mixture.purity());
+}
+
+#[test]
+fn density_matrix_measurement() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mixed = DensityMatrix::mixture(&[(0.75, Ket2::up()), (0.25, Ket2::right())]).expect("weights sum up to 1");
+
+    let probabilities = mixed.probabilities();
+    assert_approx_eq!(probabilities[0], 0.875);
+    assert_approx_eq!(probabilities[1], 0.125);
+
+    let probabilities = mixed.probabilities_in(&Basis::x());
+    assert_approx_eq!(probabilities[0], 0.625);
+
+    let (probability, state) = mixed.project(&(Ket2::down() * Bra2::down())).expect("down is possible");
+    assert_approx_eq!(probability, 0.125);
+    assert_close(state.0.iter(), DensityMatrix::from(Ket2::down()).0.iter());
+    assert!(DensityMatrix::from(Ket2::up()).project(&(Ket2::down() * Bra2::down())).is_err());
+
+    let (outcome, state) = mixed.measure(&mut rng);
+    assert!(state.is_pure());
+    assert_approx_eq!(state.probabilities()[outcome], 1.0);
+
+    let (outcome, state) = mixed.measure_in(&Basis::y(), &mut rng);
+    assert_approx_eq!(state.probabilities_in(&Basis::y())[outcome], 1.0);
+}
+
+#[test]
+fn density_matrix_to_ket() {
+    let ket = Ket2::out() * Complex::new(0.0, 1.0);
+    let recovered = DensityMatrix::from(ket).to_ket().expect("state is pure");
+
+    // the global phase is fixed to make the first amplitude real
+    assert_close(recovered.0.iter(), Ket2::out().0.iter());
+
+    let bell = Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+    assert_close(DensityMatrix::from(bell.clone()).to_ket().expect("state is pure").0.iter(), bell.0.iter());
+
+    assert!(DensityMatrix::<U2>::maximally_mixed().to_ket().is_err());
+}
+
+#[test]
+fn partial_trace_bell() {
+    let bell = Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+
+    let first: DensityMatrix<U2> = bell.reduced_state(&[0]).expect("qubit 0 is in the register");
+    let second: DensityMatrix<U2> = bell.reduced_state(&[1]).expect("qubit 1 is in the register");
+
+    assert_close(first.0.iter(), DensityMatrix::<U2>::maximally_mixed().0.iter());
+    assert_close(second.0.iter(), DensityMatrix::<U2>::maximally_mixed().0.iter());
+
+    // keeping everything is the identity map
+    let whole: DensityMatrix<U4> = bell.reduced_state(&[0, 1]).expect("qubits 0, 1 are in the register");
+    assert_close(whole.0.iter(), DensityMatrix::from(bell.clone()).0.iter());
+
+    assert!(bell.reduced_state::<U2>(&[2]).is_err());
+    assert!(bell.reduced_state::<U4>(&[0, 0]).is_err());
+    assert!(bell.reduced_state::<U4>(&[0]).is_err());
+}
+
+#[test]
+fn partial_trace_product_states() {
+    let a = Ket2::inw();
+    let b = Ket2::left();
+    let c = Ket2::up();
+    let product = a.clone().tensor(b.clone()).tensor(c.clone());
+
+    let reduced: DensityMatrix<U2> = product.reduced_state(&[1]).expect("qubit 1 is in the register");
+    assert_close(reduced.0.iter(), DensityMatrix::from(b.clone()).0.iter());
+
+    // kept qubits follow the given order
+    let swapped: DensityMatrix<U4> = product.reduced_state(&[2, 0]).expect("qubits 2, 0 are in the register");
+    assert_close(swapped.0.iter(), DensityMatrix::from(c.tensor(a)).0.iter());
+
+    // qubit ⊗ qutrit
+    let qutrit: Ket<U3> = Vector3::new(0.6.into(), 0.0.into(), Complex::new(0.0, 0.8)).into();
+    let mixed = b.clone().tensor(qutrit.clone());
+    let reduced: DensityMatrix<U3> = mixed.reduced_state_subsystems(&[2, 3], &[1]).expect("subsystem 1 exists");
+    assert_close(reduced.0.iter(), DensityMatrix::from(qutrit).0.iter());
+    let reduced: DensityMatrix<U2> = mixed.reduced_state_subsystems(&[2, 3], &[0]).expect("subsystem 0 exists");
+    assert_close(reduced.0.iter(), DensityMatrix::from(b).0.iter());
+    assert!(mixed.reduced_state_subsystems::<U2>(&[2, 2], &[0]).is_err());
+}
+
+#[test]
+fn partial_trace_operators() {
+    // Tr_B(A ⊗ B) = Tr(B) A
+    let a = Outer2::y2();
+    let b = Outer2::identity() * 3.0 + Outer2::n2();
+    let product = a.clone().tensor(b);
+
+    let reduced: Outer2 = product.partial_trace(&[0]).expect("qubit 0 is in the register");
+    assert_close(reduced.0.iter(), (&a * 6.0).0.iter());
+
+    let ghz = Circuit::new(3).h(0).cnot(0, 1).cnot(1, 2).run(&Ket2::up().tensor(Ket2::up()).tensor(Ket2::up())).expect("8 dim state fits 3 qubits");
+    let pair: DensityMatrix<U4> = DensityMatrix::from(ghz).partial_trace(&[0, 2]).expect("qubits 0, 2 are in the register");
+    let expected = DensityMatrix::mixture(&[
+        (0.5, Ket2::up().cross(Ket2::up())),
+        (0.5, Ket2::down().cross(Ket2::down())),
+    ]).expect("weights sum up to 1");
+    assert_close(pair.0.iter(), expected.0.iter());
+}
+
+#[test]
+fn schmidt_decomposition() {
+    let bell = Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+    let schmidt = bell.schmidt_decomposition(2).expect("2 divides 4");
+    assert_eq!(schmidt.rank(), 2);
+    assert_approx_eq!(schmidt.coefficients[0], SQRT_2_INVERSE);
+    assert_approx_eq!(schmidt.coefficients[1], SQRT_2_INVERSE);
+    assert_approx_eq!(bell.entanglement_entropy(2).expect("2 divides 4"), 1.0);
+
+    // Σ c_k |a_k> ⊗ |b_k> gives back the state
+    let state: Ket4 = Vector4::new(0.5.into(), Complex::new(0.0, 0.5), 0.1.into(), Complex::new(0.3, -0.2)).into();
+    let schmidt = state.schmidt_decomposition(2).expect("2 divides 4");
+    let reconstructed = (0..schmidt.rank())
+        .map(|k| schmidt.first[k].tensor(&schmidt.second[k]) * Complex::from(schmidt.coefficients[k]))
+        .fold(DKet::basis(4, 0).expect("0 < 4") * Complex::from(0.0), |acc, term| acc + term);
+    assert_close(reconstructed.0.iter(), state.0.normalize().iter());
+
+    let product = Ket2::right().cross(Ket2::inw());
+    assert_eq!(product.schmidt_decomposition(2).expect("2 divides 4").rank(), 1);
+    assert_approx_eq!(product.entanglement_entropy(2).expect("2 divides 4"), 0.0);
+    assert!(product.schmidt_decomposition(3).is_err());
+}
+
+#[test]
+fn entanglement_measures() {
+    let bell = Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+    let product = Ket2::right().cross(Ket2::inw());
+
+    assert_approx_eq!(bell.concurrence(), 1.0);
+    assert_approx_eq!(product.concurrence(), 0.0);
+
+    let rho = DensityMatrix::from(bell.clone());
+    assert_approx_eq!(rho.concurrence(), 1.0);
+    assert_approx_eq!(rho.entropy(), 0.0);
+    assert_approx_eq!(rho.negativity(&[1]).expect("qubit 1 is in the register"), 0.5);
+    assert_approx_eq!(rho.logarithmic_negativity(&[1]).expect("qubit 1 is in the register"), 1.0);
+    assert!(!rho.is_ppt(&[1]).expect("qubit 1 is in the register"));
+
+    let separable = DensityMatrix::from(product);
+    assert_approx_eq!(separable.concurrence(), 0.0);
+    assert_approx_eq!(separable.logarithmic_negativity(&[0]).expect("qubit 0 is in the register"), 0.0);
+    assert!(separable.is_ppt(&[0]).expect("qubit 0 is in the register"));
+
+    // (Y⊗Y)ρ*(Y⊗Y) of the Werner state is itself, concurrence is max(0, (3p - 1) / 2)
+    let werner = |p: f64| DensityMatrix::<U4>::new(
+        DensityMatrix::from(bell.clone()).into_outer() * p + DensityMatrix::<U4>::maximally_mixed().into_outer() * (1.0 - p)
+    ).expect("convex mixture is a density matrix");
+    assert_approx_eq!(werner(0.8).concurrence(), 0.7);
+    assert_approx_eq!(werner(0.2).concurrence(), 0.0);
+    assert!(werner(0.2).is_ppt(&[1]).expect("qubit 1 is in the register"));
+    assert!(!werner(0.5).is_ppt(&[1]).expect("qubit 1 is in the register"));
+    assert_approx_eq!(DensityMatrix::<U4>::maximally_mixed().entropy(), 2.0);
+
+    // partial transpose of |0><1| ⊗ |1><0| on the second qubit
+    let operator = DensityMatrix::<U4>(
+        (Ket2::up() * Bra2::down()).tensor(Ket2::down() * Bra2::up()).0
+    );
+    let transposed = operator.partial_transpose(&[1]).expect("qubit 1 is in the register");
+    assert_close(transposed.0.iter(), (Ket2::up() * Bra2::down()).tensor(Ket2::up() * Bra2::down()).0.iter());
+}
+
+#[test]
+fn channel_kraus_validation() {
+    assert!(Channel::new(vec![Outer2::identity() * 0.5]).is_err());
+    assert!(Channel::new(vec![Outer2::identity() * SQRT_2_INVERSE, Outer2::z2() * SQRT_2_INVERSE]).is_ok());
+    assert!(Channel::unitary(Outer2::h2()).is_ok());
+    assert!(Channel::bit_flip(1.5).is_err());
+    assert!(Channel::pauli(0.5, 0.5, 0.5).is_err());
+}
+
+#[test]
+fn channel_noise_models() {
+    let plus = DensityMatrix::from(Ket2::right());
+    let one = DensityMatrix::from(Ket2::down());
+
+    // phase flip shrinks coherences by 1 - 2p
+    let flipped = Channel::phase_flip(0.25).expect("valid probability").apply(&plus);
+    assert_approx_eq!(flipped.0[(0, 1)].re, 0.25);
+    assert_approx_eq!(flipped.0[(0, 0)].re, 0.5);
+
+    let flipped = Channel::bit_flip(0.25).expect("valid probability").apply(&one);
+    assert_approx_eq!(flipped.0[(0, 0)].re, 0.25);
+
+    let depolarized = Channel::depolarizing(1.0).expect("valid probability").apply(&plus);
+    assert_close(depolarized.0.iter(), DensityMatrix::<U2>::maximally_mixed().0.iter());
+
+    // amplitude damping: populations decay by γ, coherences by √(1 - γ)
+    let damping = Channel::amplitude_damping(0.36).expect("valid probability");
+    assert_approx_eq!(damping.apply(&one).0[(0, 0)].re, 0.36);
+    assert_approx_eq!(damping.apply(&plus).0[(0, 1)].re, 0.4);
+
+    let dephasing = Channel::phase_damping(0.36).expect("valid probability");
+    assert_approx_eq!(dephasing.apply(&plus).0[(0, 1)].re, 0.4);
+    assert_approx_eq!(dephasing.apply(&plus).0[(1, 1)].re, 0.5);
+
+    // two bit flips compose to a bit flip of p(1 - q) + q(1 - p)
+    let composed = Channel::bit_flip(0.1).expect("valid probability")
+        .then(&Channel::bit_flip(0.2).expect("valid probability"));
+    assert_eq!(composed.kraus().len(), 4);
+    assert_approx_eq!(composed.apply(&one).0[(0, 0)].re, 0.26);
+}
+
+#[test]
+fn channel_tensor() {
+    let bell = DensityMatrix::from(Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits"));
+    let local = Channel::<U2>::identity().tensor(&Channel::depolarizing(1.0).expect("valid probability"));
+    let result = local.apply(&bell);
+    assert_close(result.0.iter(), DensityMatrix::<U4>::maximally_mixed().0.iter());
+}
+
+#[test]
+fn channel_representations() {
+    // Choi matrix of the identity channel is the unnormalized Bell projector
+    let choi = Channel::<U2>::identity().choi();
+    let bell = Ket2::up().cross(Ket2::up()) + Ket2::down().cross(Ket2::down());
+    assert_close(choi.0.iter(), (bell.clone() * Bra::from(bell)).0.iter());
+
+    let plus = DensityMatrix::from(Ket2::right());
+    let damping = Channel::amplitude_damping(0.3).expect("valid probability");
+    let restored = Channel::<U2>::from_choi(&damping.choi()).expect("Choi matrix of a channel");
+    assert_close(restored.apply(&plus).0.iter(), damping.apply(&plus).0.iter());
+    assert!(Channel::<U2>::from_choi(&(Outer4::identity() * 2.0)).is_err());
+
+    // depolarizing PTM is diag(1, 1 - p, 1 - p, 1 - p)
+    let ptm = Channel::depolarizing(0.4).expect("valid probability").pauli_transfer_matrix().expect("qubit channel");
+    for i in 0..4 {
+        for j in 0..4 {
+            let expected = if i != j { 0.0 } else if i == 0 { 1.0 } else { 0.6 };
+            assert_approx_eq!(ptm[(i, j)], expected);
+        }
+    }
+
+    let ptm = damping.pauli_transfer_matrix().expect("qubit channel");
+    let restored = Channel::<U2>::from_pauli_transfer_matrix(&ptm).expect("PTM of a channel");
+    assert_close(restored.apply(&plus).0.iter(), damping.apply(&plus).0.iter());
+    assert!(Channel::<U3>::identity().pauli_transfer_matrix().is_err());
+}
+
+#[test]
+fn hamiltonian_evolution() {
+    // Rabi oscillations under H = Ω/2 X, P(|1>) = sin²(Ωt/2)
+    let omega = 2.0;
+    let hamiltonian = Outer2::n2() * (omega / 2.0);
+    let times: Vec<f64> = (0..20).map(|i| i as f64 * 0.1).collect();
+    let states = Ket2::up().evolve_at(&hamiltonian, &times).expect("hamiltonian is hermitian");
+    for (t, state) in times.iter().zip(states.iter()) {
+        assert_approx_eq!(state.probabilities()[1], (omega * t / 2.0).sin().powi(2));
+    }
+
+    // spin precession under H = ω/2 Z, <X> = cos ωt
+    let precession = Outer2::z2() * 1.5;
+    let state = Ket2::right().evolve(&precession, 0.7).expect("hamiltonian is hermitian");
+    assert_approx_eq!((Bra::from(state.clone()) * (Outer2::n2() * &state)).re, (3.0 * 0.7f64).cos());
+
+    // e^{-iHt} for H = π/2 (I - X) is X
+    let flip = (Outer2::identity() - Outer2::n2()) * (::std::f64::consts::PI / 2.0);
+    assert_close(flip.propagator(1.0).expect("hamiltonian is hermitian").0.iter(), Outer2::n2().0.iter());
+    assert!(Ket2::up().evolve(&Outer2::s2(), 1.0).is_err());
+}
+
+#[test]
+fn trotter_evolution() {
+    let terms = [
+        Outer2::n2().tensor(Outer2::n2()),
+        Outer2::z2().tensor(Outer2::identity()),
+        Outer2::identity().tensor(Outer2::z2()) * 0.5,
+    ];
+    let hamiltonian = terms.iter().fold(Outer4::identity() * 0.0, |acc, h| acc + h);
+    let initial = Ket2::up().cross(Ket2::right());
+    let exact = initial.evolve(&hamiltonian, 1.0).expect("hamiltonian is hermitian");
+
+    let error = |order, steps| {
+        let approximate = initial.evolve_trotter(&terms, 1.0, steps, order).expect("terms are hermitian");
+        (approximate - &exact).0.norm()
+    };
+    assert!(error(Trotter::First, 100) < 1e-2);
+    assert!(error(Trotter::Second, 100) < 1e-4);
+    assert!(error(Trotter::Second, 10) < error(Trotter::First, 10));
+    assert!(initial.evolve_trotter(&terms, 1.0, 0, Trotter::First).is_err());
+}
+
+#[test]
+fn lindblad_decay() {
+    let gamma: f64 = 0.5;
+    // σ- = |0><1|, T1 decay of the excited state
+    let lowering = Ket2::up() * Bra2::down();
+    let t1 = Lindblad::new(Outer2::identity() * 0.0, vec![&lowering * gamma.sqrt()]).expect("hamiltonian is hermitian");
+
+    let mut populations = Vec::new();
+    let excited = DensityMatrix::from(Ket2::down());
+    t1.evolve_with(&excited, 4.0, 400, |t, rho| populations.push((t, rho.probabilities()[1]))).expect("positive steps");
+    assert_eq!(populations.len(), 401);
+    for &(t, p) in &populations {
+        assert_approx_eq!(p, (-gamma * t).exp(), 1e-8);
+    }
+
+    // coherences decay with T2 = 2 T1 under pure amplitude damping
+    let plus = DensityMatrix::from(Ket2::right());
+    let rho = t1.evolve(&plus, 2.0, 200).expect("positive steps");
+    assert_approx_eq!(rho.0[(0, 1)].re, 0.5 * (-gamma * 2.0 / 2.0).exp(), 1e-8);
+
+    // pure dephasing with L = √(γ/2) Z, coherences decay as e^{-γt}
+    let t2 = Lindblad::new(Outer2::z2() * 0.3, vec![Outer2::z2() * (gamma / 2.0).sqrt()]).expect("hamiltonian is hermitian");
+    let rho = t2.evolve_adaptive(&plus, 3.0, 1e-10).expect("positive tolerance");
+    assert_approx_eq!(rho.0[(0, 1)].norm(), 0.5 * (-gamma * 3.0).exp(), 1e-8);
+    assert_approx_eq!(rho.probabilities()[0], 0.5);
+
+    assert!(Lindblad::new(Outer2::s2(), vec![]).is_err());
+    assert!(t1.evolve(&plus, 1.0, 0).is_err());
+    // tolerance under the rounding error cannot be met by any step
+    assert_eq!(t2.evolve_adaptive(&plus, 3.0, 1e-30).err(), Some("Step size underflow"));
+}
+
+#[test]
+fn lindblad_steady_state() {
+    let lowering = Ket2::up() * Bra2::down();
+    let damped = Lindblad::new(Outer2::n2() * 0.5, vec![lowering * 2.0]).expect("hamiltonian is hermitian");
+    let steady = damped.steady_state().expect("unique steady state");
+    assert_close(damped.derivative(&steady).0.iter(), (Outer2::identity() * 0.0).0.iter());
+
+    // long evolution arrives to the steady state
+    let evolved = damped.evolve_adaptive(&DensityMatrix::from(Ket2::down()), 30.0, 1e-10).expect("positive tolerance");
+    assert_close(evolved.0.iter(), steady.0.iter());
+
+    let decay = Lindblad::new(Outer2::identity() * 0.0, vec![Ket2::up() * Bra2::down()]).expect("hamiltonian is hermitian");
+    assert_close(decay.steady_state().expect("unique steady state").0.iter(), DensityMatrix::from(Ket2::up()).0.iter());
+}
+
+#[test]
+fn quantum_trajectories() {
+    let gamma: f64 = 0.5;
+    let lowering = Ket2::up() * Bra2::down();
+    let projector = Ket2::down() * Bra2::down();
+    let mut rng = StdRng::seed_from_u64(7);
+
+    // T1 decay: every trajectory jumps once, the average follows e^{-γt}
+    let decay = Trajectories::new(Outer2::identity() * 0.0, vec![&lowering * gamma.sqrt()]).expect("hamiltonian is hermitian");
+    let averages = decay.average(&Ket2::down(), ::std::slice::from_ref(&projector), 4.0, 40, 2000, &mut rng).expect("positive counts");
+    assert_eq!(averages.len(), 41);
+    for (i, row) in averages.iter().enumerate() {
+        assert_approx_eq!(row[0], (-gamma * i as f64 * 0.1).exp(), 0.05);
+    }
+
+    // driven damped qubit agrees with the master equation
+    let hamiltonian = Outer2::n2() * 1.0;
+    let jumps = vec![&lowering * gamma.sqrt()];
+    let trajectories = Trajectories::new(hamiltonian.clone(), jumps.clone()).expect("hamiltonian is hermitian");
+    let averages = trajectories.average(&Ket2::up(), &[projector.clone(), Outer2::y2()], 3.0, 300, 1000, &mut rng).expect("positive counts");
+
+    let mut expected = Vec::new();
+    Lindblad::new(hamiltonian, jumps).expect("hamiltonian is hermitian")
+        .evolve_with(&DensityMatrix::from(Ket2::up()), 3.0, 300, |_, rho| expected.push((rho.expectation(&projector).re, rho.expectation(&Outer2::y2()).re)))
+        .expect("positive steps");
+    for (row, &(population, y)) in averages.iter().zip(expected.iter()).step_by(30) {
+        assert_approx_eq!(row[0], population, 0.05);
+        assert_approx_eq!(row[1], y, 0.07);
+    }
+
+    let effective = decay.effective_hamiltonian();
+    assert_approx_eq!(effective.0[(1, 1)].im, -gamma / 2.0);
+    assert!(decay.average(&Ket2::down(), &[projector], 1.0, 10, 0, &mut rng).is_err());
+}
+
+#[test]
+fn bloch_vectors() {
+    let poles = [
+        (Ket2::up(), Vector3::new(0.0, 0.0, 1.0)),
+        (Ket2::down(), Vector3::new(0.0, 0.0, -1.0)),
+        (Ket2::right(), Vector3::new(1.0, 0.0, 0.0)),
+        (Ket2::left(), Vector3::new(-1.0, 0.0, 0.0)),
+        (Ket2::inw(), Vector3::new(0.0, 1.0, 0.0)),
+        (Ket2::out(), Vector3::new(0.0, -1.0, 0.0)),
+    ];
+    for (ket, expected) in &poles {
+        let vector = ket.bloch_vector();
+        for i in 0..3 {
+            assert_approx_eq!(vector[i], expected[i]);
+        }
+    }
+
+    let ket = Ket2::from_bloch(1.1, -0.4);
+    let vector = ket.bloch_vector();
+    assert_approx_eq!(vector.x, 1.1f64.sin() * (-0.4f64).cos());
+    assert_approx_eq!(vector.y, 1.1f64.sin() * (-0.4f64).sin());
+    assert_approx_eq!(vector.z, 1.1f64.cos());
+    assert_close(Ket2::from_bloch(::std::f64::consts::FRAC_PI_2, ::std::f64::consts::FRAC_PI_2).0.iter(), Ket2::inw().0.iter());
+
+    // mixed states are inside the ball
+    assert_approx_eq!(DensityMatrix::<U2>::maximally_mixed().bloch_vector().norm(), 0.0);
+    let r = Vector3::new(0.3, -0.2, 0.5);
+    let rho = DensityMatrix::from_bloch_vector(&r).expect("vector is inside the ball");
+    assert_approx_eq!((rho.bloch_vector() - r).norm(), 0.0);
+    assert!(DensityMatrix::from_bloch_vector(&Vector3::new(1.0, 1.0, 0.0)).is_err());
+}
+
+#[test]
+fn unitary_rotations() {
+    let (axis, angle) = Outer2::rx2(0.8).rotation().expect("unitary");
+    assert_approx_eq!(axis.x, 1.0);
+    assert_approx_eq!(angle, 0.8);
+
+    // global phase does not matter: Z is a π rotation around z, H around (x + z)/√2
+    let (axis, angle) = Outer2::z2().rotation().expect("unitary");
+    assert_approx_eq!(axis.z.abs(), 1.0);
+    assert_approx_eq!(angle, ::std::f64::consts::PI);
+    let (axis, angle) = Outer2::h2().rotation().expect("unitary");
+    assert_approx_eq!(axis.x.abs(), SQRT_2_INVERSE);
+    assert_approx_eq!(axis.z.abs(), SQRT_2_INVERSE);
+    assert_approx_eq!(angle, ::std::f64::consts::PI);
+
+    let (axis, angle) = (Outer2::ry2(-0.5) * Complex::i()).rotation().expect("unitary");
+    assert_approx_eq!(axis.y, -1.0);
+    assert_approx_eq!(angle, 0.5);
+
+    // rotating the Bloch vector of a state by the angle around the axis
+    let (axis, angle) = Outer2::t2().rotation().expect("unitary");
+    assert_approx_eq!(axis.z, 1.0);
+    let rotated = (Outer2::t2() * Ket2::right()).bloch_vector();
+    assert_approx_eq!(rotated.x, angle.cos());
+    assert_approx_eq!(rotated.y, angle.sin());
+
+    assert_eq!(Outer2::identity().rotation().expect("unitary").1, 0.0);
+    assert!((Outer2::identity() * 2.0).rotation().is_err());
+}
+
+#[test]
+fn dirac_notation() {
+    let bell = Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+    assert_eq!(format!("{}", bell), "0.707|00⟩ + 0.707|11⟩");
+    assert_eq!(format!("{:.2}", bell), "0.71|00⟩ + 0.71|11⟩");
+    assert_eq!(format!("{}", Bra2::up().tensor(Bra2::down())), "⟨01|");
+    assert_eq!(format!("{}", Ket2::left()), "0.707|0⟩ - 0.707|1⟩");
+    assert_eq!(format!("{}", DKet::from(Ket2::down())), "|1⟩");
+
+    // bra coefficients are conjugated
+    assert_eq!(format!("{}", Bra::from(Ket2::inw())), "0.707⟨0| - 0.707i⟨1|");
+    assert_eq!(format!("{}", Outer2::y2()), "-i|0⟩⟨1| + i|1⟩⟨0|");
+    assert_eq!(format!("{}", Outer4::cnot()), "|00⟩⟨00| + |01⟩⟨01| + |10⟩⟨11| + |11⟩⟨10|");
+    assert_eq!(format!("{}", Outer2::identity() * 0.0), "0");
+    assert_eq!(format!("{}", DensityMatrix::pure(&Ket2::right())), "0.500|0⟩⟨0| + 0.500|0⟩⟨1| + 0.500|1⟩⟨0| + 0.500|1⟩⟨1|");
+    assert_eq!(format!("{:.1}", DensityMatrix::<U2>::maximally_mixed()), "0.5|0⟩⟨0| + 0.5|1⟩⟨1|");
+
+    let state = (Ket2::up() + Ket2::down() * Complex::i()) * Complex::new(0.5, 0.5);
+    assert_eq!(format!("{}", state), "(0.500+0.500i)|0⟩ + (-0.500+0.500i)|1⟩");
+    assert_eq!(
+        state.to_dirac(&DiracFormat::new().polar(true).precision(2)),
+        "0.71e^(0.79i)|0⟩ + 0.71e^(2.36i)|1⟩"
+    );
+    assert_eq!(
+        Ket2::up().cross(Ket2::left()).to_dirac(&DiracFormat::new().labels(Labels::Integers).unicode(false).precision(1)),
+        "0.7|0> - 0.7|1>"
+    );
+    assert_eq!(
+        Ket2::up().to_dirac(&DiracFormat::new().drop_zeros(false)),
+        "|0⟩ + 0.000|1⟩"
+    );
+    let qutrit: Ket<U3> = Vector3::new(0.0.into(), 1.0.into(), 0.0.into()).into();
+    assert_eq!(format!("{}", qutrit), "|1⟩");
+}
+
+#[test]
+fn latex_output() {
+    let format = DiracFormat::new();
+    let bell = Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+    assert_eq!(bell.to_latex(LatexStyle::Dirac, &format), "0.707\\ket{00} + 0.707\\ket{11}");
+    assert_eq!(
+        Ket2::left().to_latex(LatexStyle::Matrix, &format),
+        "\\begin{pmatrix} 0.707 \\\\ -0.707 \\end{pmatrix}"
+    );
+    assert_eq!(Bra::from(Ket2::inw()).to_latex(LatexStyle::Dirac, &format), "0.707\\bra{0} - 0.707i\\bra{1}");
+    assert_eq!(
+        Bra::from(Ket2::inw()).to_latex(LatexStyle::Matrix, &format.precision(1)),
+        "\\begin{pmatrix} 0.7 & -0.7i \\end{pmatrix}"
+    );
+    assert_eq!(Outer2::n2().to_latex(LatexStyle::Dirac, &format), "\\ket{0}\\bra{1} + \\ket{1}\\bra{0}");
+    assert_eq!(
+        Outer2::s2().to_latex(LatexStyle::Matrix, &format.precision(0)),
+        "\\begin{pmatrix} 1 & 0 \\\\ 0 & 1i \\end{pmatrix}"
+    );
+    assert_eq!(
+        DKet::from(Ket2::right() * Complex::new(0.0, 1.0)).to_latex(LatexStyle::Dirac, &format.polar(true).precision(2)),
+        "0.71e^{1.57i}\\ket{0} + 0.71e^{1.57i}\\ket{1}"
+    );
+}
+
+#[test]
+fn quantikz_diagram() {
+    let mut circuit = Circuit::new(3);
+    circuit.h(0).sdg(2).cnot(0, 1).rz(2, 0.5).cz(0, 2).swap(1, 2).toffoli(0, 2, 1);
+    circuit.gate("U", Outer4::iswap(), &[1, 2]).expect("valid qubits");
+    // 1×1 gate matches 2^0, but zero-qubit gates cannot be drawn
+    assert!(circuit.gate("G", DOuter::identity(1), &[]).is_err());
+    assert_eq!(circuit.to_quantikz(), [
+        "\\begin{quantikz}",
+        "\\lstick{$q_{0}$} & \\gate{H} & \\ctrl{1} & \\ctrl{2} & \\qw & \\ctrl{1} & \\qw & \\qw \\\\",
+        "\\lstick{$q_{1}$} & \\qw & \\targ{} & \\qw & \\swap{1} & \\targ{} & \\gate[2]{U} & \\qw \\\\",
+        "\\lstick{$q_{2}$} & \\gate{S^\\dagger} & \\gate{R_z} & \\control{} & \\targX{} & \\ctrl{-1} & \\qw & \\qw",
+        "\\end{quantikz}",
+    ].join("\n"));
+
+    let inverse = Circuit::new(2).sx(1).cy(1, 0).inverse();
+    assert_eq!(inverse.to_quantikz(), [
+        "\\begin{quantikz}",
+        "\\lstick{$q_{0}$} & \\gate{Y^\\dagger} & \\qw & \\qw \\\\",
+        "\\lstick{$q_{1}$} & \\ctrl{-1} & \\gate{\\sqrt{X}^\\dagger} & \\qw",
+        "\\end{quantikz}",
+    ].join("\n"));
+
+    // arbitrary gates are boxes whatever the name, special characters are escaped
+    let mut custom = Circuit::new(2);
+    custom.gate("CNOT", Outer4::iswap(), &[0, 1]).expect("valid qubits");
+    custom.gate("U_1&2%#\\", Outer2::h2(), &[1]).expect("valid qubit");
+    assert_eq!(custom.to_quantikz(), [
+        "\\begin{quantikz}",
+        "\\lstick{$q_{0}$} & \\gate[2]{CNOT} & \\qw & \\qw \\\\",
+        "\\lstick{$q_{1}$} & \\qw & \\gate{U\\_1\\&2\\%\\#\\backslash{}} & \\qw",
+        "\\end{quantikz}",
+    ].join("\n"));
+}
+
+#[test]
+fn parse_dirac_expressions() {
+    let scalar = |input: &str| match parse::evaluate(input).expect("valid expression") {
+        parse::Value::Scalar(s) => s,
+        other => panic!("{} should be a scalar, got {:?}", input, other),
+    };
+    assert_approx_eq!(scalar("<u|d>").norm(), 0.0);
+    assert_approx_eq!(scalar("<0|0>").re, 1.0);
+    assert_approx_eq!(scalar("<r|u>").re, SQRT_2_INVERSE);
+    assert_approx_eq!(scalar("<i|o>").norm(), 0.0);
+    assert_approx_eq!(scalar("<+|(|0><1|)|->").re, -0.5);
+
+    assert_eq!(parse::evaluate("|1>").expect("valid expression"), parse::Value::Ket(DKet::from(Ket2::down())));
+    assert_eq!(parse::evaluate("<l|").expect("valid expression"), parse::Value::Bra(DBra::from(Bra2::left())));
+    assert_eq!(parse::evaluate("|u><d|").expect("valid expression"), parse::Value::Outer(DOuter::from(Ket2::up() * Bra2::down())));
+    assert_eq!(parse::evaluate(" |0> |1> ").expect("valid expression"), parse::Value::Ket(DKet::from(Ket2::up().cross(Ket2::down()))));
+    match parse::evaluate("|u><d| |d>").expect("valid expression") {
+        parse::Value::Ket(ket) => assert_close(ket.0.iter(), Ket2::up().0.iter()),
+        other => panic!("should be a ket, got {:?}", other),
+    }
+
+    let expr = parse::parse("<u|d>").expect("valid expression");
+    assert_eq!(expr.span, parse::Span { start: 0, end: 5 });
+    match expr.kind {
+        parse::ExprKind::Binary(parse::BinaryOp::Mul, ref bra, ref ket) => {
+            assert_eq!(bra.kind, parse::ExprKind::Bra("u".to_owned()));
+            assert_eq!(ket.kind, parse::ExprKind::Ket("d".to_owned()));
+            assert_eq!(ket.span, parse::Span { start: 2, end: 5 });
+        },
+        ref other => panic!("should be a product, got {:?}", other),
+    }
+}
+
+#[test]
+fn parse_errors() {
+    let error = |input: &str| parse::evaluate(input).expect_err("invalid expression");
+    let span = |start, end| parse::Span { start, end };
+
+    assert_eq!(error("<u|$>"), parse::ParseError { kind: parse::ErrorKind::UnknownLabel('$'), span: span(3, 4) });
+    assert_eq!(error("|u> ?"), parse::ParseError { kind: parse::ErrorKind::UnexpectedCharacter('?'), span: span(4, 5) });
+    assert_eq!(error("<u"), parse::ParseError { kind: parse::ErrorKind::UnterminatedBra, span: span(0, 2) });
+    assert_eq!(error("|u"), parse::ParseError { kind: parse::ErrorKind::UnterminatedKet, span: span(0, 2) });
+    assert_eq!(error("|>").kind, parse::ErrorKind::EmptyLabel);
+    assert_eq!(error("(|u>").kind, parse::ErrorKind::UnclosedParenthesis);
+    assert_eq!(error("|u>)").kind, parse::ErrorKind::UnexpectedToken);
+    assert_eq!(error("").kind, parse::ErrorKind::UnexpectedEnd);
+    assert_eq!(error("|1ψ>"), parse::ParseError { kind: parse::ErrorKind::UnknownLabel('ψ'), span: span(2, 4) });
+    assert_eq!(error("<uu|u>").kind, parse::ErrorKind::InvalidOperands("dimensions do not match"));
+    assert_eq!(error("|u> (|u><d|)").span, span(0, 12));
+    assert_eq!(format!("{}", error("<u")), "bra is not closed with '|' at 0..2");
+    assert_eq!(error("<0| |0000000000000000000000000000>"), parse::ParseError { kind: parse::ErrorKind::LabelTooLong, span: span(4, 34) });
+    assert!(parse::evaluate(&format!("|{}>", "+".repeat(parse::MAX_LABEL_QUBITS))).is_ok());
+
+    let too_large = parse::ErrorKind::InvalidOperands("result is too large");
+    assert_eq!(error("|0000000000>|0000000000>|0000000000>"), parse::ParseError { kind: too_large.clone(), span: span(0, 24) });
+    assert_eq!(error("<000000|<00000|").kind, too_large);
+    assert_eq!(error(&["H"; 14].join("⊗")).kind, too_large);
+    assert!(parse::evaluate(&["H"; parse::MAX_QUBITS].join("⊗")).is_ok());
+}
+
+#[test]
+fn parse_arithmetic() {
+    let ket = |input: &str| match parse::evaluate(input).expect("valid expression") {
+        parse::Value::Ket(ket) => ket,
+        other => panic!("{} should be a ket, got {:?}", input, other),
+    };
+    let scalar = |input: &str| match parse::evaluate(input).expect("valid expression") {
+        parse::Value::Scalar(s) => s,
+        other => panic!("{} should be a scalar, got {:?}", input, other),
+    };
+
+    let state = ket("0.6|00> + 0.8i|11>");
+    assert_eq!(state.dim(), 4);
+    assert_close(state.0.iter(), [0.6.into(), 0.0.into(), 0.0.into(), Complex::new(0.0, 0.8)].iter());
+    assert_close(ket("(|0> - |1>)/sqrt(2)").0.iter(), Ket2::left().0.iter());
+    assert_close(ket("|+->").0.iter(), Ket2::right().cross(Ket2::left()).0.iter());
+    assert_close(ket("-i |1> * 2 - |0>").0.iter(), [(-1.0).into(), Complex::new(0.0, -2.0)].iter());
+    assert_close(ket("exp(i pi/2)|0>").0.iter(), [Complex::i(), 0.0.into()].iter());
+
+    match parse::evaluate("<ud|").expect("valid expression") {
+        parse::Value::Bra(bra) => assert_close(bra.0.iter(), Bra2::up().tensor(Bra2::down()).0.iter()),
+        other => panic!("should be a bra, got {:?}", other),
+    }
+
+    // precedence: 1 + 2 * 3 - 4 / 2 = 5, unary minus binds tighter than products
+    assert_approx_eq!(scalar("1 + 2 * 3 - 4 / 2").re, 5.0);
+    assert_approx_eq!(scalar("2 - -3").re, 5.0);
+    assert_approx_eq!(scalar("(1 + 2i)(1 - 2i)").re, 5.0);
+    assert_approx_eq!(scalar("<00|(0.6|00> + 0.8|11>)").re, 0.6);
+    assert_approx_eq!(scalar("(<0| + <1|)(|0> + |1>)/2").re, 1.0);
+}
+
+#[test]
+fn parse_arithmetic_errors() {
+    let error = |input: &str| parse::evaluate(input).expect_err("invalid expression");
+    let span = |start, end| parse::Span { start, end };
+
+    assert_eq!(error("|0> + |00>"), parse::ParseError { kind: parse::ErrorKind::InvalidOperands("dimensions do not match"), span: span(0, 10) });
+    assert_eq!(error("|0> + 1").kind, parse::ErrorKind::InvalidOperands("only values of the same kind can be added"));
+    assert_eq!(error("1 / |0>"), parse::ParseError { kind: parse::ErrorKind::InvalidOperands("scalar is expected"), span: span(4, 7) });
+    assert_eq!(error("|0> / 0").kind, parse::ErrorKind::InvalidOperands("division by zero"));
+    assert_eq!(error("sqrt(|0>)").span, span(5, 8));
+    assert_eq!(error("1.2.3"), parse::ParseError { kind: parse::ErrorKind::InvalidNumber, span: span(0, 5) });
+    assert_eq!(error("2 foo"), parse::ParseError { kind: parse::ErrorKind::UnknownIdentifier("foo".to_owned()), span: span(2, 5) });
+    assert_eq!(error("sqrt 2").kind, parse::ErrorKind::UnexpectedToken);
+    assert_eq!(error("|0> +").kind, parse::ErrorKind::UnexpectedEnd);
+    assert_eq!(error("(|0> + |1>"), parse::ParseError { kind: parse::ErrorKind::UnclosedParenthesis, span: span(0, 1) });
+}
+
+#[test]
+fn parse_named_operators() {
+    let scalar = |input: &str| match parse::evaluate(input).expect("valid expression") {
+        parse::Value::Scalar(s) => s,
+        other => panic!("{} should be a scalar, got {:?}", input, other),
+    };
+    assert_approx_eq!(scalar("<0|H|1>").re, SQRT_2_INVERSE);
+    assert_approx_eq!(scalar("<1|X|0>").re, 1.0);
+    assert_approx_eq!(scalar("<+|Z|->").re, 1.0);
+    assert_approx_eq!(scalar("<0|S†S|0>").re, 1.0);
+    assert_approx_eq!(scalar("(|i>)†|i>").re, 1.0);
+    assert_approx_eq!(scalar("<0|(|1><0|)†|1>").re, 1.0);
+
+    match parse::evaluate("CNOT (H⊗I) |00>").expect("valid expression") {
+        parse::Value::Ket(ket) => {
+            let bell = Circuit::new(2).h(0).cnot(0, 1).run(&Ket2::up().cross(Ket2::up())).expect("4 dim state fits 2 qubits");
+            assert_close(ket.0.iter(), bell.0.iter());
+        },
+        other => panic!("should be a ket, got {:?}", other),
+    }
+    assert_eq!(parse::evaluate("Y†").expect("valid expression"), parse::Value::Outer(Outer2::y2().dagger().into()));
+    assert_eq!(parse::evaluate("|0>⊗|1>").expect("valid expression"), parse::evaluate("|01>").expect("valid expression"));
+    assert_eq!(parse::evaluate("<+|X|+>").expect("valid expression").to_string(), "1.000");
+    assert_eq!(format!("{:.1}", parse::evaluate("<0|Y|1>").expect("valid expression")), "-1.0i");
+
+    // expectation values of user defined states and operators
+    let mut context = parse::Context::new();
+    let psi = context.evaluate_str("(|0> + i|1>)/sqrt(2)").expect("valid expression");
+    assert!(context.define("ψ", psi).is_none());
+    context.register_operator("Sy", Outer2::y2() / 2.0);
+    match context.evaluate_str("<ψ|Sy|ψ>").expect("valid expression") {
+        parse::Value::Scalar(s) => assert_approx_eq!(s.re, 0.5),
+        other => panic!("should be a scalar, got {:?}", other),
+    }
+    assert!(context.get("Sy").is_some());
+    assert!(parse::Context::empty().evaluate_str("H").is_err());
+
+    // names made of basis symbols are labels in brackets
+    assert!(parse::is_label("lol") && parse::is_label("0+"));
+    assert!(!parse::is_label("ψ") && !parse::is_label("lo_l") && !parse::is_label(""));
+}
+
+#[test]
+fn parse_named_operator_errors() {
+    let error = |input: &str| parse::evaluate(input).expect_err("invalid expression");
+    let span = |start, end| parse::Span { start, end };
+
+    assert_eq!(error("<ψ|H|0>"), parse::ParseError { kind: parse::ErrorKind::UnknownIdentifier("ψ".to_owned()), span: span(0, 4) });
+    assert_eq!(error("H<0|"), parse::ParseError { kind: parse::ErrorKind::InvalidOperands("operator cannot act on this side"), span: span(0, 4) });
+    assert_eq!(error("|H>").kind, parse::ErrorKind::InvalidOperands("name in brackets should refer to a ket"));
+    assert_eq!(error("H |00>").kind, parse::ErrorKind::InvalidOperands("dimensions do not match"));
+    assert_eq!(error("|0> ⊗ H").kind, parse::ErrorKind::InvalidOperands("tensor product of different kinds"));
+    assert_eq!(error("<0|0> ⊗ |0>"), parse::ParseError {
+        kind: parse::ErrorKind::InvalidOperands("tensor product of different kinds"),
+        span: parse::Span { start: 0, end: 13 },
+    });
+    assert_eq!(error("2 ⊗ |0>").kind, parse::ErrorKind::InvalidOperands("tensor product of different kinds"));
+    assert_eq!(error("|0$>"), parse::ParseError { kind: parse::ErrorKind::UnknownLabel('$'), span: span(2, 3) });
+    assert_eq!(error("†").kind, parse::ErrorKind::UnexpectedToken);
+}
+
+#[test]
+fn dirac_literals() {
+    let bell: Ket4 = ket!(SQRT_2_INVERSE|00> + SQRT_2_INVERSE|11>);
+    assert_eq!(bell, (Ket2::up().cross(Ket2::up()) + Ket2::down().cross(Ket2::down())) * Complex::from(SQRT_2_INVERSE));
+    let _: Ket<U8> = ket!(|010>);
+    assert_eq!(ket!(|+->), Ket2::right().cross(Ket2::left()));
+    assert_eq!(ket!(0.6|0> - 0.8|1>), Ket2::up() * Complex::from(0.6) - Ket2::down() * Complex::from(0.8));
+    assert_eq!(bra!(<-|), Bra2::left());
+    assert_eq!(bra!(<+|), Bra2::right());
+    assert_eq!(bra!((Complex::i())<1|) * ket!(|1>), Complex::i());
+    assert_eq!(op!(|0><1|), Ket2::up() * Bra2::down());
+    assert_eq!(op!(|+><-| - 2.0|1><0|), Ket2::right() * Bra2::left() - Ket2::down() * Bra2::up() * Complex::from(2.0));
+    let _: Outer4 = op!(|00><00| + |11><11|);
+}
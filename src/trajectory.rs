@@ -0,0 +1,115 @@
+use nalgebra::{DefaultAllocator, DimName, MatrixMN, U1};
+use nalgebra::allocator::Allocator;
+use rand::Rng;
+
+use {linalg, measure, Ket, Outer, Complex};
+
+/// Monte Carlo wavefunction (quantum jump) unravelling of the Lindblad master equation.
+///
+/// Every trajectory evolves a ket under the effective non-hermitian hamiltonian
+/// `H - i/2 Σ C†C`, interrupted by random jumps `|ψ> → C|ψ>`; averages over trajectories
+/// converge to the density matrix solution with the same collapse (jump) operators.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trajectories<D: DimName>
+    where DefaultAllocator: Allocator<Complex, D, D>
+{
+    hamiltonian: Outer<D>,
+    collapse: Vec<Outer<D>>,
+}
+
+impl<D: DimName> Trajectories<D>
+    where DefaultAllocator: Allocator<Complex, D> + Allocator<Complex, D, D> + Allocator<Complex, U1, D>
+{
+    /// Trajectories engine with the hamiltonian (should be hermitian) and collapse operators
+    /// (including the rates, e.g. `√γ σ-`)
+    pub fn new(hamiltonian: Outer<D>, collapse: Vec<Outer<D>>) -> Result<Trajectories<D>, &'static str> {
+        if !linalg::is_hermitian(&linalg::to_dynamic(&hamiltonian.0)) {
+            return Err("Hamiltonian should be hermitian");
+        }
+        Ok(Trajectories { hamiltonian, collapse })
+    }
+
+    /// Effective non-hermitian hamiltonian, `H - i/2 Σ C†C`
+    pub fn effective_hamiltonian(&self) -> Outer<D> {
+        let mut result = self.hamiltonian.0.clone();
+        for c in &self.collapse {
+            result -= c.0.adjoint() * &c.0 * Complex::new(0.0, 0.5);
+        }
+        Outer(result)
+    }
+
+    // RK4 step of d|ψ>/dt = -iH_eff|ψ> as a single operator, Σ_{k≤4} (-iH_eff dt)^k / k!
+    fn step_operator(&self, dt: f64) -> MatrixMN<Complex, D, D> {
+        let generator = self.effective_hamiltonian().0 * Complex::new(0.0, -dt);
+        let mut term = MatrixMN::identity_generic(D::name(), D::name());
+        let mut result = term.clone();
+        for k in 1..=4 {
+            term = &term * &generator / Complex::from(k as f64);
+            result += &term;
+        }
+        result
+    }
+
+    /// Single trajectory from the `initial` state over time `t` with `steps` fixed steps.
+    ///
+    /// `callback` is called with the time and the normalized state at the start and after every
+    /// step. Returns the normalized final state.
+    pub fn trajectory<R: Rng, F>(&self, initial: &Ket<D>, t: f64, steps: usize, rng: &mut R, mut callback: F) -> Result<Ket<D>, &'static str>
+        where F: FnMut(f64, &Ket<D>)
+    {
+        if steps == 0 {
+            return Err("Integration needs at least one step");
+        }
+        let dt = t / steps as f64;
+        let step = self.step_operator(dt);
+
+        // norm of the unnormalized state decays until it crosses the random threshold, then a jump happens
+        let mut state = initial.0.normalize();
+        let mut threshold: f64 = rng.gen();
+        callback(0.0, &Ket(state.clone()));
+
+        for i in 1..=steps {
+            state = &step * state;
+            if state.norm_squared() < threshold {
+                let jumped: Vec<_> = self.collapse.iter().map(|c| &c.0 * &state).collect();
+                let weights: Vec<f64> = jumped.iter().map(|v| v.norm_squared()).collect();
+                let total: f64 = weights.iter().sum();
+                // without collapse operators the norm only drifts with the integration error
+                if total > 0.0 {
+                    let chosen = measure::pick(&weights.iter().map(|w| w / total).collect::<Vec<_>>(), rng);
+                    state = jumped[chosen].normalize();
+                }
+                threshold = rng.gen();
+            }
+            callback(i as f64 * dt, &Ket(state.normalize()));
+        }
+
+        Ok(Ket(state.normalize()))
+    }
+
+    /// Expectation values of the `observables` averaged over `count` trajectories from the
+    /// `initial` state.
+    ///
+    /// Returns a row of values for each of the `steps + 1` times `i * t / steps`, in the order
+    /// of the observables.
+    pub fn average<R: Rng>(&self, initial: &Ket<D>, observables: &[Outer<D>], t: f64, steps: usize, count: usize, rng: &mut R) -> Result<Vec<Vec<f64>>, &'static str> {
+        if count == 0 {
+            return Err("Averaging needs at least one trajectory");
+        }
+
+        let mut sums = vec![vec![0.0; observables.len()]; steps + 1];
+        for _ in 0..count {
+            let mut i = 0;
+            self.trajectory(initial, t, steps, rng, |_, state| {
+                for (sum, observable) in sums[i].iter_mut().zip(observables) {
+                    *sum += state.0.dotc(&(&observable.0 * &state.0)).re;
+                }
+                i += 1;
+            })?;
+        }
+
+        Ok(sums.into_iter()
+            .map(|row| row.into_iter().map(|sum| sum / count as f64).collect())
+            .collect())
+    }
+}
@@ -0,0 +1,96 @@
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+// Run the `braket` binary on the script, returning its output
+fn run(name: &str, script: &str) -> Output {
+    let path = env::temp_dir().join(format!("braket-{}-{}.bk", name, std::process::id()));
+    fs::write(&path, script).expect("temporary directory is writable");
+    let output = Command::new(env!("CARGO_BIN_EXE_braket")).arg(&path).output().expect("braket runs");
+    fs::remove_file(&path).expect("script was written");
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("output is UTF-8")
+}
+
+#[test]
+fn script_bindings() {
+    let output = run("bindings", "\
+# comments and blank lines are skipped
+
+let psi = (|0> + |1>)/sqrt(2)
+  let   bell =CNOT (psi ⊗ |0>)
+<psi|X|psi>
+:probs bell
+:bloch psi
+:history
+");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "\
+psi = 0.707|0⟩ + 0.707|1⟩
+bell = 0.707|00⟩ + 0.707|11⟩
+1.000
+|00⟩: 0.5000
+|01⟩: 0.0000
+|10⟩: 0.0000
+|11⟩: 0.5000
+(1.0000, 0.0000, 0.0000)
+   1  let psi = (|0> + |1>)/sqrt(2)
+   2  let   bell =CNOT (psi ⊗ |0>)
+   3  <psi|X|psi>
+   4  :probs bell
+   5  :bloch psi
+   6  :history
+");
+}
+
+#[test]
+fn script_measurements() {
+    let script = ":seed 7\n:measure (|0> + |1>)/sqrt(2)   1000\n:measure |1> 10\n";
+    let output = run("measurements", script);
+    assert!(output.status.success());
+
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    let counts: Vec<usize> = lines[..2].iter()
+        .map(|line| line.split(": ").nth(1).and_then(|count| count.parse().ok()).expect("label: count"))
+        .collect();
+    assert!(lines[0].starts_with("|0⟩: ") && lines[1].starts_with("|1⟩: "));
+    assert_eq!(counts.iter().sum::<usize>(), 1000);
+    assert_eq!(lines[2], "|1⟩: 10");
+
+    // same seed gives same counts
+    assert_eq!(stdout(&run("measurements-again", script)), text);
+}
+
+#[test]
+fn script_errors() {
+    let output = run("parse-error", "let x = |0>\nlet y = |0> +\n:probs x\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "x = |0⟩\n");
+    let error = String::from_utf8(output.stderr).expect("output is UTF-8");
+    assert!(error.ends_with(":2: ERROR: unexpected end of input at 13..13\nlet y = |0> +\n             ^\n"), "{}", error);
+
+    for (script, message) in &[
+        ("let 1x = |0>", "Invalid name `1x`"),
+        ("let pi = 3", "Name `pi` is reserved"),
+        ("let sqrt = 2", "Name `sqrt` is reserved"),
+        ("let i = |0>", "Name `i` is reserved"),
+        ("let u = |1>", "Name `u` is made of basis symbols, |u> would be a basis state"),
+        ("let lol = |1>", "Name `lol` is made of basis symbols, |lol> would be a basis state"),
+        ("let CNOT = I", "Name `CNOT` is a built-in operator"),
+        (":measure |0>", "Expected `:measure EXPR N`"),
+        (":measure |0> 0", "Number of shots should be a positive integer"),
+        (":measure H 10", "Expression should evaluate to a ket"),
+        (":seed -1", "Seed should be a non-negative integer"),
+        (":bloch |00>", "Bloch vector is defined only for a single qubit"),
+    ] {
+        let output = run("command-error", script);
+        assert_eq!(output.status.code(), Some(1));
+        let error = String::from_utf8(output.stderr).expect("output is UTF-8");
+        assert!(error.trim_end().ends_with(message), "{}: {}", script, error);
+    }
+}